@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::calculator::{language_by_name, Detail};
+use crate::error::Error;
+use crate::json_printer::SCHEMA_VERSION;
+use crate::Result;
+
+#[derive(Deserialize)]
+struct SavedReport {
+    schema: u32,
+    sections: Vec<SavedDetail>,
+    summary: SavedDetail,
+}
+
+#[derive(Deserialize)]
+struct SavedDetail {
+    language: String,
+    files: usize,
+    bytes: u64,
+    blank: usize,
+    comment: usize,
+    code: usize,
+}
+
+impl From<SavedDetail> for Detail {
+    fn from(saved: SavedDetail) -> Self {
+        #[rustfmt::skip]
+        let SavedDetail { language, files, bytes, blank, comment, code } = saved;
+        Detail::new(language_by_name(&language), files, bytes, blank, comment, code)
+    }
+}
+
+/// A report saved earlier with `--output json`, compared against by `--baseline` and added up by `sum-reports`.
+#[derive(Debug)]
+pub struct Baseline {
+    pub path: PathBuf,
+    pub sections: HashMap<&'static str, Detail>,
+    pub summary: Detail,
+}
+
+impl Baseline {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::load_impl(path.as_ref())
+    }
+
+    fn load_impl(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let saved = serde_json::from_str::<SavedReport>(&content)?;
+
+        if saved.schema > SCHEMA_VERSION {
+            return Err(Error::InvalidArg(format!(
+                "{} uses report schema {}, this rcloc only understands up to {}",
+                path.display(),
+                saved.schema,
+                SCHEMA_VERSION
+            )));
+        }
+
+        let sections = saved
+            .sections
+            .into_iter()
+            .map(Detail::from)
+            .map(|detail| (detail.language, detail))
+            .collect();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            sections,
+            summary: Detail::from(saved.summary),
+        })
+    }
+
+    /// The saved counts of `language`, all zero when it did not exist back then.
+    pub fn previous(&self, language: &'static str) -> Detail {
+        self.sections
+            .get(language)
+            .copied()
+            .unwrap_or_else(|| Detail::new(language, 0, 0, 0, 0, 0))
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Delta {
+    pub files: i64,
+    pub bytes: i64,
+    pub blank: i64,
+    pub comment: i64,
+    pub code: i64,
+    /// Change of code lines relative to the baseline, `None` when the baseline had none.
+    pub code_percent: Option<f64>,
+}
+
+impl Delta {
+    pub fn new(previous: &Detail, current: &Detail) -> Self {
+        let diff = |previous: u64, current: u64| current as i64 - previous as i64;
+        let code = diff(previous.code as u64, current.code as u64);
+
+        Self {
+            files: diff(previous.files as u64, current.files as u64),
+            bytes: diff(previous.bytes, current.bytes),
+            blank: diff(previous.blank as u64, current.blank as u64),
+            comment: diff(previous.comment as u64, current.comment as u64),
+            code,
+            code_percent: if previous.code == 0 {
+                None
+            } else {
+                Some(code as f64 * 100_f64 / previous.code as f64)
+            },
+        }
+    }
+
+    /// Signed code delta and percentage as shown in the tables, e.g. `+1240` and `+12.34%`.
+    pub fn code_columns(&self) -> (String, String) {
+        let percent = match self.code_percent {
+            Some(percent) => format!("{:+.2}%", percent),
+            None if self.code == 0 => String::from("-"),
+            None => String::from("new"),
+        };
+        (format!("{:+}", self.code), percent)
+    }
+}
@@ -1,138 +1,320 @@
 #![allow(clippy::useless_vec)]
 use std::collections::HashMap;
 use std::ffi::OsStr;
-use std::fs;
+use std::fs::{self, File};
+use std::io::Read;
 use std::ops::{Add, AddAssign};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
 
 use lazy_static::lazy_static;
 
+use crate::encoding::{decode, looks_binary, Encoding, SNIFF_SIZE};
+use crate::error::Error;
 use crate::executor::ThreadPoolExecutor;
+use crate::filter::Filter;
+use crate::reporter::UnknownFiles;
 use crate::Result;
 
 pub struct Calculator {
     filename_receiver: Receiver<PathBuf>,
-    detail_sender: SyncSender<Detail>,
+    detail_sender: SyncSender<Counted>,
     executor: ThreadPoolExecutor,
+    filter: Filter,
+    max_file_size: Option<u64>,
+    report_unknown: bool,
 }
 
 impl Calculator {
-    pub fn new(filename_receiver: Receiver<PathBuf>) -> (Self, Receiver<Detail>) {
-        let (detail_sender, detail_receiver) = sync_channel::<Detail>(32);
+    pub fn new(
+        filename_receiver: Receiver<PathBuf>,
+        filter: Filter,
+        max_file_size: Option<u64>,
+        report_unknown: bool,
+    ) -> (Self, Receiver<Counted>) {
+        let (detail_sender, detail_receiver) = sync_channel::<Counted>(32);
 
         let calculator = Self {
             filename_receiver,
             detail_sender,
             executor: ThreadPoolExecutor::new(),
+            filter,
+            max_file_size,
+            report_unknown,
         };
 
         (calculator, detail_receiver)
     }
 
-    pub fn calculate(self) {
+    /// Counts every file received, returns how many were left out for being larger than --max-file-size and,
+    /// with --report-unknown, the files of no known language.
+    pub fn calculate(self) -> (usize, Option<Vec<UnknownFiles>>) {
         let Calculator {
             filename_receiver,
             detail_sender,
             executor,
+            filter,
+            max_file_size,
+            report_unknown,
         } = self;
+        let filter = Arc::new(filter);
+        let too_large = Arc::new(AtomicUsize::new(0));
+        let unknown = Arc::new(Mutex::new(HashMap::<String, UnknownFiles>::new()));
 
         for filename in filename_receiver {
+            // Files without an extension are looked up by their `#!` line, which the job reads off this thread.
+            let by_extension = match filename.extension() {
+                Some(ext) => match MANAGER.get_by_extension(ext) {
+                    Some(info) if filter.accepts_language(info.language) => Some(info),
+                    Some(_) => continue,
+                    None => {
+                        if report_unknown {
+                            Self::tally_unknown(&mut unknown.lock().unwrap(), &filename);
+                        }
+                        continue;
+                    }
+                },
+                None => None,
+            };
+
             let sender = SyncSender::clone(&detail_sender);
+            let filter = Arc::clone(&filter);
+            let too_large = Arc::clone(&too_large);
+            let unknown = Arc::clone(&unknown);
             executor.submit(move || {
-                filename
-                    .extension()
-                    .and_then(|ext| MANAGER.get_by_extension(ext))
-                    .and_then(|info| Self::statistical_detail(filename, info).ok())
-                    .and_then(|detail| sender.send(detail).ok());
+                let info = match by_extension.or_else(|| Self::shebang_info(&filename)) {
+                    Some(info) if filter.accepts_language(info.language) => info,
+                    Some(_) => return,
+                    None => {
+                        if report_unknown {
+                            Self::tally_unknown(&mut unknown.lock().unwrap(), &filename);
+                        }
+                        return;
+                    }
+                };
+                if let Some(max) = max_file_size {
+                    if filename.metadata().is_ok_and(|metadata| metadata.len() > max) {
+                        too_large.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                }
+
+                let counted = match Self::statistical_detail(&filename, info) {
+                    Ok(Some((detail, encoding))) => Counted::File(FileDetail {
+                        path: filename,
+                        detail,
+                        encoding,
+                    }),
+                    Ok(None) => Counted::Binary(filename),
+                    Err(error) => Counted::Failed(FileError { path: filename, error }),
+                };
+                let _ = sender.send(counted);
             });
         }
+        // waits for the jobs, which tally into `too_large` and `unknown`
+        drop(executor);
+
+        let unknown = if report_unknown {
+            let mut unknown = unknown
+                .lock()
+                .unwrap()
+                .drain()
+                .map(|(_, files)| files)
+                .collect::<Vec<_>>();
+            unknown.sort_by(|prev, next| next.files.cmp(&prev.files).then_with(|| prev.name.cmp(&next.name)));
+            Some(unknown)
+        } else {
+            None
+        };
+        (too_large.load(Ordering::Relaxed), unknown)
+    }
+
+    fn info_of(filename: &Path) -> Option<&'static Info> {
+        match filename.extension() {
+            Some(ext) => MANAGER.get_by_extension(ext),
+            None => Self::shebang_info(filename),
+        }
+    }
+
+    // Scripts often go without an extension, their `#!` line names the interpreter instead.
+    fn shebang_info(filename: &Path) -> Option<&'static Info> {
+        let mut head = vec![];
+        File::open(filename)
+            .ok()?
+            .take(SHEBANG_SIZE)
+            .read_to_end(&mut head)
+            .ok()?;
+        let first_line = head.split(|&byte| byte == b'\n').next()?;
+        MANAGER.get_by_shebang(&String::from_utf8_lossy(first_line))
+    }
+
+    fn tally_unknown(unknown: &mut HashMap<String, UnknownFiles>, filename: &Path) {
+        let name = match filename.extension() {
+            Some(ext) => format!(".{}", ext.to_string_lossy()),
+            None => filename
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        let bytes = filename.metadata().map(|metadata| metadata.len()).unwrap_or_default();
+
+        let files = unknown.entry(name).or_insert_with_key(|name| UnknownFiles {
+            name: name.clone(),
+            files: 0,
+            bytes: 0,
+        });
+        files.files += 1;
+        files.bytes += bytes;
     }
 
     #[inline]
-    fn statistical_detail<P: AsRef<Path> + Sync + Send>(filename: P, info: &Info) -> Result<Detail> {
+    fn statistical_detail<P: AsRef<Path> + Sync + Send>(
+        filename: P,
+        info: &Info,
+    ) -> Result<Option<(Detail, Encoding)>> {
         Self::statistical_detail_impl(filename.as_ref(), info)
     }
 
-    fn statistical_detail_impl(filename: &Path, info: &Info) -> Result<Detail> {
-        #[rustfmt::skip]
-        let Info { language, single, multi, .. } = info;
+    // `None` for binary content, which is told apart by its first block so that the rest is never read.
+    fn statistical_detail_impl(filename: &Path, info: &Info) -> Result<Option<(Detail, Encoding)>> {
+        let mut file = File::open(filename)?;
+        let mut bytes = vec![];
+        file.by_ref().take(SNIFF_SIZE).read_to_end(&mut bytes)?;
+        if looks_binary(&bytes) {
+            return Ok(None);
+        }
+        file.read_to_end(&mut bytes)?;
+
+        let size = bytes.len() as u64;
+        let (content, encoding) = decode(bytes);
+
+        Ok(Some((Self::count_lines(&content, size, info), encoding)))
+    }
+
+    /// Counts source that does not come from a file, as the language named `language`, or else the language
+    /// of `filename`'s extension, or of the interpreter on its `#!` line when it has none.
+    pub fn content_detail(content: &str, language: Option<&str>, filename: Option<&Path>) -> Result<Detail> {
+        let info = match (language, filename) {
+            (Some(name), _) => find_language(name)
+                .and_then(|language| MANAGER.languages.get(language))
+                .ok_or_else(|| Error::InvalidArg(format!("unknown language `{}`", name)))?,
+            (None, Some(filename)) => match filename.extension() {
+                Some(ext) => MANAGER.get_by_extension(ext),
+                None => MANAGER.get_by_shebang(content.lines().next().unwrap_or_default()),
+            }
+            .ok_or_else(|| Error::InvalidArg(format!("no language known for `{}`", filename.display())))?,
+            (None, None) => return Err(Error::InvalidArg("no language given".to_string())),
+        };
 
-        let content = fs::read_to_string(&filename)?;
-        let metadata = filename.metadata()?;
-        let bytes = metadata.len();
+        Ok(Self::count_lines(content, content.len() as u64, info))
+    }
+
+    fn count_lines(content: &str, bytes: u64, info: &Info) -> Detail {
         let mut blank = 0;
         let mut comment = 0;
         let mut code = 0;
         let mut in_comment: Option<(&str, &str)> = None;
 
-        'here: for line in content.lines() {
-            let line = line.trim();
+        for line in content.lines() {
+            match Self::classify(line.trim(), info, &mut in_comment) {
+                LineKind::Blank => blank += 1,
+                LineKind::Comment => comment += 1,
+                LineKind::Code => code += 1,
+            }
+        }
+
+        Detail::new(info.language, 1, bytes, blank, comment, code)
+    }
+
+    /// Reads a file of a known language and classifies each of its lines, paired with the trimmed line itself.
+    pub fn classify_lines(filename: &Path) -> Option<Vec<(LineKind, String)>> {
+        let info = Self::info_of(filename)?;
+        let (content, _) = decode(fs::read(filename).ok()?);
+        let mut in_comment: Option<(&str, &str)> = None;
+
+        let lines = content
+            .lines()
+            .map(|line| {
+                let line = line.trim();
+                (Self::classify(line, info, &mut in_comment), line.to_string())
+            })
+            .collect();
+
+        Some(lines)
+    }
+
+    // `in_comment` carries the delimiters of a multi line comment that is still open from previous lines.
+    fn classify<'a>(line: &str, info: &'a Info, in_comment: &mut Option<(&'a str, &'a str)>) -> LineKind {
+        #[rustfmt::skip]
+        let Info { single, multi, .. } = info;
+
+        // empty line
+        if line.is_empty() {
+            return LineKind::Blank;
+        }
 
-            // empty line
-            if line.is_empty() {
-                blank += 1;
-                continue;
+        // match single line comments
+        for single in single {
+            if line.starts_with(single) {
+                return LineKind::Comment;
             }
+        }
 
-            // match single line comments
-            for single in single {
-                if line.starts_with(single) {
-                    comment += 1;
-                    continue 'here;
+        // match multi line comments
+        for (start, end) in multi {
+            if let Some(d) = *in_comment {
+                if d != (*start, *end) {
+                    continue;
                 }
             }
 
-            // match multi line comments
-            for (start, end) in multi {
-                if let Some(d) = in_comment {
-                    if d != (*start, *end) {
-                        continue;
+            // multi line comments maybe in one line
+            let mut same_line = false;
+            if line.starts_with(start) {
+                *in_comment = match *in_comment {
+                    Some(_) => {
+                        *in_comment = None;
+                        return LineKind::Comment;
                     }
-                }
-
-                // multi line comments maybe in one line
-                let mut same_line = false;
-                if line.starts_with(start) {
-                    in_comment = match in_comment {
-                        Some(_) => {
-                            comment += 1;
-                            in_comment = None;
-                            continue 'here;
-                        }
-                        None => {
-                            same_line = true;
-                            Some((start, end))
-                        }
+                    None => {
+                        same_line = true;
+                        Some((start, end))
                     }
                 }
+            }
 
-                // This line is in comments
-                if in_comment.is_some() {
-                    comment += 1;
-                    if line.ends_with(end) {
-                        if same_line {
-                            if line.len() >= (start.len() + end.len()) {
-                                in_comment = None;
-                            }
-                        } else {
-                            in_comment = None;
+            // This line is in comments
+            if in_comment.is_some() {
+                if line.ends_with(end) {
+                    if same_line {
+                        if line.len() >= (start.len() + end.len()) {
+                            *in_comment = None;
                         }
+                    } else {
+                        *in_comment = None;
                     }
-                    continue 'here;
                 }
+                return LineKind::Comment;
             }
-
-            code += 1;
         }
 
-        Ok(Detail::new(language, 1, bytes, blank, comment, code))
+        LineKind::Code
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineKind {
+    Blank,
+    Comment,
+    Code,
+}
+
 #[derive(Debug)]
 struct Info {
     language: &'static str,
+    #[allow(dead_code)]
     file_ext: Vec<&'static str>,
     single: Vec<&'static str>,
     multi: Vec<(&'static str, &'static str)>,
@@ -149,8 +331,12 @@ impl Info {
 struct Manager {
     languages: HashMap<&'static str, Info>,
     ext_to_language: HashMap<&'static str, &'static str>,
+    interpreter_to_language: HashMap<&'static str, &'static str>,
 }
 
+// how much of a file without a known extension is read to find its `#!` line
+const SHEBANG_SIZE: u64 = 256;
+
 impl Manager {
     #[inline]
     fn get_by_extension(&self, ext: &OsStr) -> Option<&Info> {
@@ -158,6 +344,40 @@ impl Manager {
             .and_then(|ext| self.ext_to_language.get(ext))
             .and_then(|language| self.languages.get(language))
     }
+
+    // `#!/usr/bin/python3`, `#!/usr/bin/env -S ruby -w` or `#!/bin/sh`, versions like `python3.11` are ignored.
+    fn get_by_shebang(&self, first_line: &str) -> Option<&Info> {
+        let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+        let mut program = Path::new(words.next()?).file_name()?.to_str()?;
+        if program == "env" {
+            let word = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+            program = Path::new(word).file_name()?.to_str()?;
+        }
+
+        let unversioned = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        self.interpreter_to_language
+            .get(program)
+            .or_else(|| self.interpreter_to_language.get(unversioned))
+            .and_then(|language| self.languages.get(language))
+    }
+}
+
+/// The language called `name`, ignoring case, as named in reports.
+pub fn find_language(name: &str) -> Option<&'static str> {
+    MANAGER
+        .languages
+        .keys()
+        .find(|language| language.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+/// Resolves a language name read back from a saved report to the name `Detail` carries.
+pub fn language_by_name(name: &str) -> &'static str {
+    match MANAGER.languages.get_key_value(name) {
+        Some((language, _)) => language,
+        // Unknown to this build, e.g. written by a newer rcloc, so it has to live until the process exits.
+        None => Box::leak(name.to_string().into_boxed_str()),
+    }
 }
 
 #[rustfmt::skip]
@@ -300,7 +520,28 @@ lazy_static! {
         language!("Zig", vec!["zig"], vec!["//"]);
         language!("Zsh", vec!["zsh"], vec!["#"]);
 
-        Manager { languages, ext_to_language }
+        let interpreter_to_language = vec![
+            ("bash", "Bash"),
+            ("sh", "Shell"), ("dash", "Shell"), ("ash", "Shell"), ("ksh", "Shell"), ("mksh", "Shell"),
+            ("zsh", "Zsh"),
+            ("python", "Python"), ("pypy", "Python"),
+            ("ruby", "Ruby"),
+            ("perl", "Perl"), ("perl6", "Rakudo"), ("raku", "Rakudo"),
+            ("lua", "Lua"), ("luajit", "Lua"),
+            ("node", "JavaScript"), ("nodejs", "JavaScript"),
+            ("php", "Php"),
+            ("Rscript", "R"),
+            ("julia", "Julia"),
+            ("elixir", "Elixir"),
+            ("groovy", "Groovy"),
+            ("scala", "Scala"),
+            ("pwsh", "PowerShell"),
+            ("crystal", "Crystal"),
+            ("racket", "Racket"),
+            ("guile", "Scheme"),
+        ].into_iter().collect();
+
+        Manager { languages, ext_to_language, interpreter_to_language }
     };
 }
 
@@ -327,6 +568,29 @@ impl Detail {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct FileDetail {
+    pub path: PathBuf,
+    pub detail: Detail,
+    pub encoding: Encoding,
+}
+
+/// A file of a known language that could not be read.
+#[derive(Debug)]
+pub struct FileError {
+    pub path: PathBuf,
+    pub error: Error,
+}
+
+/// What counting a single file came to.
+#[derive(Debug)]
+pub enum Counted {
+    File(FileDetail),
+    // skipped, whatever its extension says
+    Binary(PathBuf),
+    Failed(FileError),
+}
+
 impl Add for Detail {
     type Output = Detail;
 
@@ -351,3 +615,42 @@ impl AddAssign for Detail {
         self.code += rhs.code;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_detail() {
+        let content = "// main\nfn main() {\n\n    /* nothing */\n}\n";
+        let detail = Calculator::content_detail(content, Some("rust"), None).unwrap();
+        assert_eq!(detail.language, "Rust");
+        assert_eq!(detail.bytes, content.len() as u64);
+        assert_eq!((detail.blank, detail.comment, detail.code), (1, 2, 2));
+
+        let detail = Calculator::content_detail("x = 1\n", None, Some(Path::new("src/app.py"))).unwrap();
+        assert_eq!(detail.language, "Python");
+
+        assert!(Calculator::content_detail("", Some("Klingon"), None).is_err());
+        assert!(Calculator::content_detail("", None, Some(Path::new("Makefile"))).is_err());
+
+        let script = "#!/usr/bin/perl\nprint 1;\n";
+        let detail = Calculator::content_detail(script, None, Some(Path::new("bin/deploy"))).unwrap();
+        assert_eq!(detail.language, "Perl");
+        assert!(Calculator::content_detail(script, None, Some(Path::new("run.cgi"))).is_err());
+    }
+
+    #[test]
+    fn test_get_by_shebang() {
+        let language = |line: &str| MANAGER.get_by_shebang(line).map(|info| info.language);
+
+        assert_eq!(language("#!/usr/bin/env python3"), Some("Python"));
+        assert_eq!(language("#!/usr/bin/python3.11 -u"), Some("Python"));
+        assert_eq!(language("#! /bin/bash -e"), Some("Bash"));
+        assert_eq!(language("#!/bin/sh"), Some("Shell"));
+        assert_eq!(language("#!/usr/bin/env -S LANG=C ruby -w"), Some("Ruby"));
+        assert_eq!(language("#!/usr/bin/env perl6"), Some("Rakudo"));
+        assert_eq!(language("#!/usr/bin/awk -f"), None);
+        assert_eq!(language("import os"), None);
+    }
+}
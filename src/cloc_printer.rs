@@ -0,0 +1,368 @@
+use std::time::Duration;
+
+use crate::baseline::Delta;
+use crate::calculator::Detail;
+use crate::differ::{DiffDetail, DiffReport};
+use crate::reporter::{DirectoryDetail, Metadata, Report, UnknownFiles};
+use crate::util::escape_markup;
+
+// Mirrors the `header` section of Perl cloc's --yaml and --xml reports.
+struct Header {
+    elapsed_seconds: f64,
+    n_files: usize,
+    n_lines: usize,
+    files_per_second: f64,
+    lines_per_second: f64,
+}
+
+impl Header {
+    fn new(summary: &Detail, elapsed: Duration) -> Self {
+        let elapsed_seconds = elapsed.as_secs_f64();
+        let n_files = summary.files;
+        let n_lines = summary.blank + summary.comment + summary.code;
+        let per_second = |n: usize| {
+            if elapsed_seconds > 0_f64 {
+                n as f64 / elapsed_seconds
+            } else {
+                0_f64
+            }
+        };
+
+        Self {
+            elapsed_seconds,
+            n_files,
+            n_lines,
+            files_per_second: per_second(n_files),
+            lines_per_second: per_second(n_lines),
+        }
+    }
+}
+
+const CLOC_URL: &str = "github.com/ltoddy/cloc-rs";
+
+pub fn yaml_print(report: Report, metadata: Metadata) {
+    let Report {
+        sections,
+        files,
+        directories,
+        summary,
+        sources,
+        baseline,
+        unknown,
+        ..
+    } = report;
+
+    yaml_print_header(&Header::new(&summary, metadata.elapsed));
+
+    match (files, directories.or(sources)) {
+        (Some(files), _) => {
+            for file in &files {
+                let detail = &file.detail;
+                println!("{} :", yaml_key(&file.path.to_string_lossy()));
+                println!("  blank: {}", detail.blank);
+                println!("  comment: {}", detail.comment);
+                println!("  code: {}", detail.code);
+                println!("  language: {}", yaml_key(detail.language));
+                println!("  encoding: {}", file.encoding);
+            }
+        }
+        (None, Some(directories)) => {
+            for directory in &directories {
+                let detail = &directory.total;
+                println!("{} :", yaml_key(&directory.path.to_string_lossy()));
+                println!("  nFiles: {}", detail.files);
+                println!("  blank: {}", detail.blank);
+                println!("  comment: {}", detail.comment);
+                println!("  code: {}", detail.code);
+                if !directory.languages.is_empty() {
+                    println!("  languages :");
+                }
+                for detail in &directory.languages {
+                    println!("    {} :", yaml_key(detail.language));
+                    println!("      nFiles: {}", detail.files);
+                    println!("      blank: {}", detail.blank);
+                    println!("      comment: {}", detail.comment);
+                    println!("      code: {}", detail.code);
+                }
+            }
+        }
+        (None, None) => {
+            for detail in &sections {
+                println!("{} :", yaml_key(detail.language));
+                println!("  nFiles: {}", detail.files);
+                println!("  blank: {}", detail.blank);
+                println!("  comment: {}", detail.comment);
+                println!("  code: {}", detail.code);
+                if let Some(baseline) = &baseline {
+                    yaml_print_delta(&Delta::new(&baseline.previous(detail.language), detail));
+                }
+            }
+        }
+    }
+
+    println!("SUM:");
+    println!("  blank: {}", summary.blank);
+    println!("  comment: {}", summary.comment);
+    println!("  code: {}", summary.code);
+    println!("  nFiles: {}", summary.files);
+    if let Some(baseline) = &baseline {
+        yaml_print_delta(&Delta::new(&baseline.summary, &summary));
+    }
+    if let Some(unknown) = unknown {
+        println!("unknown :");
+        for files in &unknown {
+            println!("  {} :", yaml_key(&files.name));
+            println!("    nFiles: {}", files.files);
+            println!("    bytes: {}", files.bytes);
+        }
+    }
+}
+
+fn yaml_print_delta(delta: &Delta) {
+    println!("  delta :");
+    println!("    nFiles: {}", delta.files);
+    println!("    blank: {}", delta.blank);
+    println!("    comment: {}", delta.comment);
+    println!("    code: {}", delta.code);
+    if let Some(percent) = delta.code_percent {
+        println!("    code_percent: {:.2}", percent);
+    }
+}
+
+fn yaml_print_header(header: &Header) {
+    println!("---");
+    println!("# {}", CLOC_URL);
+    println!("header :");
+    println!("  cloc_url           : {}", CLOC_URL);
+    println!("  cloc_version       : {}", env!("CARGO_PKG_VERSION"));
+    println!("  elapsed_seconds    : {:.6}", header.elapsed_seconds);
+    println!("  n_files            : {}", header.n_files);
+    println!("  n_lines            : {}", header.n_lines);
+    println!("  files_per_second   : {:.6}", header.files_per_second);
+    println!("  lines_per_second   : {:.6}", header.lines_per_second);
+}
+
+pub fn xml_print(report: Report, metadata: Metadata) {
+    let Report {
+        sections,
+        files,
+        directories,
+        summary,
+        sources,
+        baseline,
+        unknown,
+        ..
+    } = report;
+
+    println!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><results>");
+    xml_print_header(&Header::new(&summary, metadata.elapsed));
+
+    match (files, directories, sources) {
+        (Some(files), _, _) => {
+            println!("<files>");
+            for file in &files {
+                let detail = &file.detail;
+                println!(
+                    "  <file name=\"{}\" blank=\"{}\" comment=\"{}\" code=\"{}\" language=\"{}\" encoding=\"{}\" />",
+                    escape_markup(&file.path.to_string_lossy()),
+                    detail.blank,
+                    detail.comment,
+                    detail.code,
+                    escape_markup(detail.language),
+                    file.encoding,
+                );
+            }
+            println!(
+                "  <total blank=\"{}\" comment=\"{}\" code=\"{}\" />",
+                summary.blank, summary.comment, summary.code,
+            );
+            println!("</files>");
+        }
+        (None, Some(directories), _) => xml_print_directories(directories, summary, ("directories", "directory")),
+        (None, None, Some(sources)) => xml_print_directories(sources, summary, ("sources", "source")),
+        (None, None, None) => {
+            let delta = |detail: &Detail, previous: Option<Detail>| {
+                previous
+                    .map(|previous| xml_delta_attributes(&Delta::new(&previous, detail)))
+                    .unwrap_or_default()
+            };
+            println!("<languages>");
+            for detail in &sections {
+                let previous = baseline.as_ref().map(|baseline| baseline.previous(detail.language));
+                println!(
+                    "  <language name=\"{}\" files_count=\"{}\" blank=\"{}\" comment=\"{}\" code=\"{}\"{} />",
+                    escape_markup(detail.language),
+                    detail.files,
+                    detail.blank,
+                    detail.comment,
+                    detail.code,
+                    delta(detail, previous),
+                );
+            }
+            println!(
+                "  <total sum_files=\"{}\" blank=\"{}\" comment=\"{}\" code=\"{}\"{} />",
+                summary.files,
+                summary.blank,
+                summary.comment,
+                summary.code,
+                delta(&summary, baseline.as_ref().map(|baseline| baseline.summary)),
+            );
+            println!("</languages>");
+        }
+    }
+
+    if let Some(unknown) = unknown {
+        xml_print_unknown(&unknown);
+    }
+    println!("</results>");
+}
+
+fn xml_print_unknown(unknown: &[UnknownFiles]) {
+    println!("<unknown>");
+    for files in unknown {
+        println!(
+            "  <file_type name=\"{}\" files_count=\"{}\" bytes=\"{}\" />",
+            escape_markup(&files.name),
+            files.files,
+            files.bytes,
+        );
+    }
+    println!("</unknown>");
+}
+
+// `(plural, singular)` element names, the same layout serves directories and the sources of summed reports.
+fn xml_print_directories(directories: Vec<DirectoryDetail>, summary: Detail, (group, element): (&str, &str)) {
+    println!("<{}>", group);
+    for directory in &directories {
+        let detail = &directory.total;
+        let attributes = format!(
+            "name=\"{}\" files_count=\"{}\" blank=\"{}\" comment=\"{}\" code=\"{}\"",
+            escape_markup(&directory.path.to_string_lossy()),
+            detail.files,
+            detail.blank,
+            detail.comment,
+            detail.code,
+        );
+        if directory.languages.is_empty() {
+            println!("  <{} {} />", element, attributes);
+            continue;
+        }
+        println!("  <{} {}>", element, attributes);
+        for detail in &directory.languages {
+            println!(
+                "    <language name=\"{}\" files_count=\"{}\" blank=\"{}\" comment=\"{}\" code=\"{}\" />",
+                escape_markup(detail.language),
+                detail.files,
+                detail.blank,
+                detail.comment,
+                detail.code,
+            );
+        }
+        println!("  </{}>", element);
+    }
+    println!(
+        "  <total sum_files=\"{}\" blank=\"{}\" comment=\"{}\" code=\"{}\" />",
+        summary.files, summary.blank, summary.comment, summary.code,
+    );
+    println!("</{}>", group);
+}
+
+fn xml_delta_attributes(delta: &Delta) -> String {
+    let percent = delta
+        .code_percent
+        .map(|percent| format!(" delta_code_percent=\"{:.2}\"", percent))
+        .unwrap_or_default();
+
+    format!(
+        " delta_files=\"{}\" delta_blank=\"{}\" delta_comment=\"{}\" delta_code=\"{}\"{}",
+        delta.files, delta.blank, delta.comment, delta.code, percent,
+    )
+}
+
+fn xml_print_header(header: &Header) {
+    println!("<header>");
+    println!("  <cloc_url>{}</cloc_url>", CLOC_URL);
+    println!("  <cloc_version>{}</cloc_version>", env!("CARGO_PKG_VERSION"));
+    println!("  <elapsed_seconds>{:.6}</elapsed_seconds>", header.elapsed_seconds);
+    println!("  <n_files>{}</n_files>", header.n_files);
+    println!("  <n_lines>{}</n_lines>", header.n_lines);
+    println!("  <files_per_second>{:.6}</files_per_second>", header.files_per_second);
+    println!("  <lines_per_second>{:.6}</lines_per_second>", header.lines_per_second);
+    println!("</header>");
+}
+
+// Perl cloc groups a diff by status first, then by language.
+pub fn yaml_print_diff(report: DiffReport, elapsed: Duration) {
+    let DiffReport { sections, summary, .. } = report;
+
+    yaml_print_header(&Header::new(&diff_totals(&summary), elapsed));
+
+    for (index, (status, _)) in summary.rows().iter().enumerate() {
+        println!("{} :", status);
+        for diff in &sections {
+            let detail = diff.rows()[index].1;
+            println!("  {} :", yaml_key(diff.language));
+            println!("    nFiles: {}", detail.files);
+            println!("    blank: {}", detail.blank);
+            println!("    comment: {}", detail.comment);
+            println!("    code: {}", detail.code);
+        }
+    }
+
+    println!("SUM :");
+    for (status, detail) in summary.rows().iter() {
+        println!("  {} :", status);
+        println!("    nFiles: {}", detail.files);
+        println!("    blank: {}", detail.blank);
+        println!("    comment: {}", detail.comment);
+        println!("    code: {}", detail.code);
+    }
+}
+
+pub fn xml_print_diff(report: DiffReport, elapsed: Duration) {
+    let DiffReport { sections, summary, .. } = report;
+
+    println!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><diff_results>");
+    xml_print_header(&Header::new(&diff_totals(&summary), elapsed));
+
+    for (index, (status, total)) in summary.rows().iter().enumerate() {
+        println!("<{}>", status);
+        for diff in &sections {
+            let detail = diff.rows()[index].1;
+            println!(
+                "  <language name=\"{}\" files_count=\"{}\" blank=\"{}\" comment=\"{}\" code=\"{}\" />",
+                escape_markup(diff.language),
+                detail.files,
+                detail.blank,
+                detail.comment,
+                detail.code,
+            );
+        }
+        println!(
+            "  <total sum_files=\"{}\" blank=\"{}\" comment=\"{}\" code=\"{}\" />",
+            total.files, total.blank, total.comment, total.code,
+        );
+        println!("</{}>", status);
+    }
+
+    println!("</diff_results>");
+}
+
+// The header of a diff describes the new tree: everything that was not removed.
+fn diff_totals(summary: &DiffDetail) -> Detail {
+    let mut totals = summary.same;
+    totals += summary.modified;
+    totals += summary.added;
+    totals
+}
+
+// Language names such as "C#" or "F#" would start a comment in a plain YAML scalar.
+fn yaml_key(key: &str) -> String {
+    let plain = key.chars().all(|c| c.is_ascii_alphanumeric() || " +-._/".contains(c));
+
+    if plain {
+        key.to_string()
+    } else {
+        format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
@@ -0,0 +1,325 @@
+use crate::baseline::{Baseline, Delta};
+use crate::calculator::{Detail, FileDetail};
+use crate::differ::DiffReport;
+use crate::reporter::{DirectoryDetail, Report, UnknownFiles};
+
+pub fn csv_print(report: Report, delimiter: char, with_sum: bool) {
+    for line in csv_lines(report, delimiter, with_sum) {
+        println!("{}", line);
+    }
+}
+
+fn csv_lines(report: Report, delimiter: char, with_sum: bool) -> Vec<String> {
+    let mut lines = vec![];
+    let out = &mut lines;
+    let Report {
+        sections,
+        files,
+        directories,
+        summary,
+        sources,
+        baseline,
+        unknown,
+        ..
+    } = report;
+
+    if let Some(files) = files {
+        csv_print_files(out, files, summary, delimiter, with_sum);
+    } else if let Some(directories) = directories {
+        csv_print_directories(out, directories, summary, "directory", delimiter, with_sum);
+    } else if let Some(sources) = sources {
+        csv_print_directories(out, sources, summary, "source", delimiter, with_sum);
+    } else if let Some(baseline) = baseline {
+        csv_print_baseline(out, sections, summary, baseline, delimiter, with_sum);
+    } else {
+        csv_print_languages(out, sections, summary, delimiter, with_sum);
+    }
+
+    if let Some(unknown) = unknown {
+        csv_print_unknown(out, unknown, delimiter);
+    }
+
+    lines
+}
+
+// A block of its own after an empty line, so that the report above keeps its columns.
+fn csv_print_unknown(out: &mut Vec<String>, unknown: Vec<UnknownFiles>, delimiter: char) {
+    out.push(String::new());
+    record(out, &["unknown", "files", "bytes"], &[], delimiter);
+
+    for entry in &unknown {
+        let numbers = [entry.files.to_string(), entry.bytes.to_string()];
+        record(out, &[&quote(&entry.name, delimiter)], &numbers, delimiter);
+    }
+}
+
+fn csv_print_languages(out: &mut Vec<String>, sections: Vec<Detail>, summary: Detail, delimiter: char, with_sum: bool) {
+    record(
+        out,
+        &["language", "files", "bytes", "blank", "comment", "code"],
+        &[],
+        delimiter,
+    );
+
+    for detail in &sections {
+        record(out, &[&quote(detail.language, delimiter)], &counts(detail), delimiter);
+    }
+
+    if with_sum {
+        record(out, &[&quote("Sum", delimiter)], &counts(&summary), delimiter);
+    }
+}
+
+fn csv_print_baseline(
+    out: &mut Vec<String>,
+    sections: Vec<Detail>,
+    summary: Detail,
+    baseline: Baseline,
+    delimiter: char,
+    with_sum: bool,
+) {
+    #[rustfmt::skip]
+    let header = ["language", "files", "bytes", "blank", "comment", "code", "delta_files", "delta_bytes", "delta_blank", "delta_comment", "delta_code", "delta_code_percent"];
+    record(out, &header, &[], delimiter);
+
+    let with_delta = |detail: &Detail, previous: &Detail| {
+        #[rustfmt::skip]
+        let Delta { files, bytes, blank, comment, code, code_percent } = Delta::new(previous, detail);
+        let percent = code_percent
+            .map(|percent| format!("{:.2}", percent))
+            .unwrap_or_default();
+        #[rustfmt::skip]
+        let delta = [files.to_string(), bytes.to_string(), blank.to_string(), comment.to_string(), code.to_string(), percent];
+        [counts(detail), delta.to_vec()].concat()
+    };
+
+    for detail in &sections {
+        let previous = baseline.previous(detail.language);
+        record(
+            out,
+            &[&quote(detail.language, delimiter)],
+            &with_delta(detail, &previous),
+            delimiter,
+        );
+    }
+
+    if with_sum {
+        record(
+            out,
+            &[&quote("Sum", delimiter)],
+            &with_delta(&summary, &baseline.summary),
+            delimiter,
+        );
+    }
+}
+
+fn csv_print_files(out: &mut Vec<String>, files: Vec<FileDetail>, summary: Detail, delimiter: char, with_sum: bool) {
+    record(
+        out,
+        &["file", "language", "encoding", "bytes", "blank", "comment", "code"],
+        &[],
+        delimiter,
+    );
+
+    for file in &files {
+        let path = quote(&file.path.to_string_lossy(), delimiter);
+        let language = quote(file.detail.language, delimiter);
+        let encoding = quote(&file.encoding.to_string(), delimiter);
+        record(
+            out,
+            &[&path, &language, &encoding],
+            &counts(&file.detail)[1..],
+            delimiter,
+        );
+    }
+
+    if with_sum {
+        record(
+            out,
+            &[&quote("Sum", delimiter), &quote("", delimiter), &quote("", delimiter)],
+            &counts(&summary)[1..],
+            delimiter,
+        );
+    }
+}
+
+// With a language breakdown every row is one language of one directory, so the data stays flat for pivot tables.
+fn csv_print_directories(
+    out: &mut Vec<String>,
+    directories: Vec<DirectoryDetail>,
+    summary: Detail,
+    heading: &str,
+    delimiter: char,
+    with_sum: bool,
+) {
+    let breakdown = directories.iter().any(|directory| !directory.languages.is_empty());
+
+    if breakdown {
+        record(
+            out,
+            &[heading, "language", "files", "bytes", "blank", "comment", "code"],
+            &[],
+            delimiter,
+        );
+    } else {
+        record(
+            out,
+            &[heading, "files", "bytes", "blank", "comment", "code"],
+            &[],
+            delimiter,
+        );
+    }
+
+    for directory in &directories {
+        let path = quote(&directory.path.to_string_lossy(), delimiter);
+        if breakdown {
+            for detail in &directory.languages {
+                record(
+                    out,
+                    &[&path, &quote(detail.language, delimiter)],
+                    &counts(detail),
+                    delimiter,
+                );
+            }
+        } else {
+            record(out, &[&path], &counts(&directory.total), delimiter);
+        }
+    }
+
+    if with_sum {
+        if breakdown {
+            record(
+                out,
+                &[&quote("Sum", delimiter), &quote("", delimiter)],
+                &counts(&summary),
+                delimiter,
+            );
+        } else {
+            record(out, &[&quote("Sum", delimiter)], &counts(&summary), delimiter);
+        }
+    }
+}
+
+pub fn csv_print_diff(report: DiffReport, delimiter: char, with_sum: bool) {
+    let DiffReport { sections, summary, .. } = report;
+    let mut lines = vec![];
+    let out = &mut lines;
+
+    record(
+        out,
+        &["language", "status", "files", "blank", "comment", "code"],
+        &[],
+        delimiter,
+    );
+
+    for diff in sections.iter().chain(if with_sum { Some(&summary) } else { None }) {
+        for (status, detail) in diff.rows().iter() {
+            #[rustfmt::skip]
+            let numbers = [detail.files.to_string(), detail.blank.to_string(), detail.comment.to_string(), detail.code.to_string()];
+            record(
+                out,
+                &[&quote(diff.language, delimiter), &quote(status, delimiter)],
+                &numbers,
+                delimiter,
+            );
+        }
+    }
+
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+fn record(out: &mut Vec<String>, texts: &[&str], numbers: &[String], delimiter: char) {
+    let fields = texts.iter().map(|text| text.to_string()).chain(numbers.iter().cloned());
+    out.push(fields.collect::<Vec<_>>().join(&delimiter.to_string()));
+}
+
+// files, bytes, blank, comment, code
+fn counts(detail: &Detail) -> Vec<String> {
+    #[rustfmt::skip]
+    let Detail { files, bytes, blank, comment, code, .. } = *detail;
+    vec![
+        files.to_string(),
+        bytes.to_string(),
+        blank.to_string(),
+        comment.to_string(),
+        code.to_string(),
+    ]
+}
+
+// Text fields are always quoted in CSV, so that names like "C++ Header" survive any spreadsheet import.
+// TSV has no quoting convention, so fields are only quoted when they contain a tab, a quote or a newline.
+fn quote(field: &str, delimiter: char) -> String {
+    let needs_quote = delimiter == ',' || field.contains([delimiter, '"', '\n', '\r']);
+
+    if needs_quote {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::Encoding;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("C++ Header", ','), "\"C++ Header\"");
+        assert_eq!(quote("say \"hi\"", ','), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote("Visual Studio Solution", '\t'), "Visual Studio Solution");
+        assert_eq!(quote("a\tb", '\t'), "\"a\tb\"");
+    }
+
+    #[test]
+    fn test_record_width() {
+        let rust = Detail::new("Rust", 2, 300, 10, 5, 40);
+        let unknown = UnknownFiles {
+            name: ".xyz".to_string(),
+            files: 1,
+            bytes: 12,
+        };
+        let report = |files, directories| Report {
+            sections: vec![rust],
+            files,
+            directories,
+            summary: rust,
+            sources: None,
+            baseline: None,
+            unmatched_ignores: vec![],
+            skipped: Default::default(),
+            binaries: vec![],
+            unknown: Some(vec![unknown.clone()]),
+            failures: vec![],
+        };
+        let file = FileDetail {
+            path: PathBuf::from("src/main.rs"),
+            detail: rust,
+            encoding: Encoding::Utf8,
+        };
+        let directory = |languages| DirectoryDetail {
+            path: PathBuf::from("src"),
+            total: rust,
+            languages,
+        };
+
+        let reports = vec![
+            report(None, None),
+            report(Some(vec![file]), None),
+            report(None, Some(vec![directory(vec![])])),
+            report(None, Some(vec![directory(vec![rust])])),
+        ];
+        for report in reports {
+            let lines = csv_lines(report, '\t', true);
+            for block in lines.split(|line| line.is_empty()) {
+                let width = block[0].split('\t').count();
+                for line in block {
+                    assert_eq!(line.split('\t').count(), width, "{}", line);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::fs;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+
+use crate::calculator::{Calculator, Detail, FileDetail, FileError, LineKind};
+use crate::engine::Engine;
+use crate::explorer::WalkOptions;
+use crate::reporter::{Granularity, Report, Skipped};
+use crate::Result;
+
+pub struct Differ {
+    old: PathBuf,
+    new: PathBuf,
+    walk: WalkOptions,
+}
+
+impl Differ {
+    pub fn new(old: PathBuf, new: PathBuf, walk: WalkOptions) -> Self {
+        Self { old, new, walk }
+    }
+
+    pub fn diff(self) -> Result<DiffReport> {
+        let Self { old, new, walk } = self;
+
+        let (old_files, old_report) = Self::count(&old, walk.clone())?;
+        let (mut new_files, new_report) = Self::count(&new, walk)?;
+        let (old_root, new_root) = (Engine::root_of(&old), Engine::root_of(&new));
+
+        // Two files of the same language are compared with each other, whatever their names.
+        let paired = old.is_file() && new.is_file() && Self::pair_files(&old_files, &mut new_files);
+        let new_path_of = |path: &Path| if paired { new.clone() } else { new_root.join(path) };
+
+        let mut kinds = HashMap::<&str, DiffDetail>::new();
+
+        for (path, before) in old_files {
+            match new_files.remove(&path) {
+                Some(after) => {
+                    let (old_path, new_path) = (old_root.join(&path), new_path_of(&path));
+                    let acc = Self::kind(&mut kinds, after.language);
+
+                    if Self::same_content(&old_path, &new_path) {
+                        acc.same += after;
+                        continue;
+                    }
+
+                    match (
+                        Calculator::classify_lines(&old_path),
+                        Calculator::classify_lines(&new_path),
+                    ) {
+                        (Some(old_lines), Some(new_lines)) => acc.compare(after, &old_lines, &new_lines),
+                        _ => {
+                            acc.removed += before;
+                            acc.added += after;
+                        }
+                    }
+                }
+                None => Self::kind(&mut kinds, before.language).removed += before,
+            }
+        }
+
+        for after in new_files.into_values() {
+            Self::kind(&mut kinds, after.language).added += after;
+        }
+
+        let mut summary = DiffDetail::new("Sum");
+        for detail in kinds.values() {
+            summary += *detail;
+        }
+
+        let mut skipped = old_report.skipped;
+        skipped += new_report.skipped;
+        // The ignore file applies to both trees, a pattern matching in either of them is of use.
+        let mut unmatched_ignores = old_report.unmatched_ignores;
+        unmatched_ignores.retain(|pattern| new_report.unmatched_ignores.contains(pattern));
+
+        Ok(DiffReport {
+            old,
+            new,
+            sections: kinds.into_values().collect(),
+            summary,
+            unmatched_ignores,
+            skipped,
+            binaries: old_report.binaries.into_iter().chain(new_report.binaries).collect(),
+            failures: old_report.failures.into_iter().chain(new_report.failures).collect(),
+        })
+    }
+
+    // Files one each, the new one is filed under the name of the old one.
+    fn pair_files(old_files: &HashMap<PathBuf, Detail>, new_files: &mut HashMap<PathBuf, Detail>) -> bool {
+        let (old_name, before) = match old_files.iter().next() {
+            Some(file) => file,
+            None => return false,
+        };
+        let new_name = match new_files.iter().next() {
+            Some((name, after)) if after.language == before.language => name.clone(),
+            _ => return false,
+        };
+        if let Some(after) = new_files.remove(&new_name) {
+            new_files.insert(old_name.clone(), after);
+        }
+        true
+    }
+
+    #[inline]
+    fn kind<'a>(kinds: &'a mut HashMap<&'static str, DiffDetail>, language: &'static str) -> &'a mut DiffDetail {
+        kinds.entry(language).or_insert_with(|| DiffDetail::new(language))
+    }
+
+    // The counted files by path, and the rest of the report for what was left out.
+    // Failures and binary files keep the root of their tree, as the same relative path may fail in both.
+    fn count(entry: &Path, walk: WalkOptions) -> Result<(HashMap<PathBuf, Detail>, Report)> {
+        let mut report = Engine::new(vec![entry.to_path_buf()], walk, Granularity::File)?.serve();
+        let root = Engine::root_of(entry);
+        for failure in &mut report.failures {
+            failure.path = root.join(&failure.path);
+        }
+        for path in &mut report.binaries {
+            *path = root.join(&path);
+        }
+
+        let files = report
+            .files
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|FileDetail { path, detail, .. }| (path, detail))
+            .collect();
+        Ok((files, report))
+    }
+
+    #[inline]
+    fn same_content(old: &Path, new: &Path) -> bool {
+        match (fs::read(old), fs::read(new)) {
+            (Ok(old), Ok(new)) => old == new,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct DiffDetail {
+    pub language: &'static str,
+    pub same: Detail,
+    pub modified: Detail,
+    pub added: Detail,
+    pub removed: Detail,
+}
+
+impl DiffDetail {
+    fn new(language: &'static str) -> Self {
+        let empty = Detail::new(language, 0, 0, 0, 0, 0);
+
+        Self {
+            language,
+            same: empty,
+            modified: empty,
+            added: empty,
+            removed: empty,
+        }
+    }
+
+    // Lines kept by the shortest edit script are the same. Within each run of changes, removed and
+    // added lines of the same kind are paired up as modified, whatever is left over was added or removed.
+    fn compare(&mut self, after: Detail, old: &[(LineKind, String)], new: &[(LineKind, String)]) {
+        // blank, comment, code
+        let (mut same, mut modified, mut added, mut removed) = ([0; 3], [0; 3], [0; 3], [0; 3]);
+        let (mut i, mut j) = (0, 0);
+
+        for (x, y) in longest_common_subsequence(old, new)
+            .into_iter()
+            .chain(Some((old.len(), new.len())))
+        {
+            let (gone, came) = (Self::tally(&old[i..x]), Self::tally(&new[j..y]));
+            for kind in 0..3 {
+                let paired = gone[kind].min(came[kind]);
+                modified[kind] += paired;
+                removed[kind] += gone[kind] - paired;
+                added[kind] += came[kind] - paired;
+            }
+
+            if let Some((kind, _)) = old.get(x) {
+                same[*kind as usize] += 1;
+            }
+            i = x + 1;
+            j = y + 1;
+        }
+
+        let detail = |files, bytes, [blank, comment, code]: [usize; 3]| {
+            Detail::new(after.language, files, bytes, blank, comment, code)
+        };
+        self.same += detail(0, 0, same);
+        self.modified += detail(1, after.bytes, modified);
+        self.added += detail(0, 0, added);
+        self.removed += detail(0, 0, removed);
+    }
+
+    fn tally(lines: &[(LineKind, String)]) -> [usize; 3] {
+        let mut counts = [0; 3];
+        for (kind, _) in lines {
+            counts[*kind as usize] += 1;
+        }
+        counts
+    }
+
+    /// `(status, counts)` rows in the order cloc prints them.
+    pub fn rows(&self) -> [(&'static str, &Detail); 4] {
+        [
+            ("same", &self.same),
+            ("modified", &self.modified),
+            ("added", &self.added),
+            ("removed", &self.removed),
+        ]
+    }
+}
+
+impl AddAssign for DiffDetail {
+    fn add_assign(&mut self, rhs: Self) {
+        self.same += rhs.same;
+        self.modified += rhs.modified;
+        self.added += rhs.added;
+        self.removed += rhs.removed;
+    }
+}
+
+#[derive(Debug)]
+pub struct DiffReport {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    pub sections: Vec<DiffDetail>,
+    pub summary: DiffDetail,
+    /// Patterns of --ignore-file that matched nothing in either tree.
+    pub unmatched_ignores: Vec<String>,
+    /// What the walk limits left out of both trees.
+    pub skipped: Skipped,
+    /// Files of either tree with a source extension but binary content.
+    pub binaries: Vec<PathBuf>,
+    /// Files of either tree that could not be read, left out of the comparison.
+    pub failures: Vec<FileError>,
+}
+
+/// Index pairs `(i, j)` of `old[i] == new[j]` forming a longest common subsequence, in increasing order.
+///
+/// This is Myers' O(ND) algorithm in its linear space variant: the middle snake of the shortest edit
+/// script splits the problem in two halves which are solved recursively.
+pub fn longest_common_subsequence<T: PartialEq>(old: &[T], new: &[T]) -> Vec<(usize, usize)> {
+    let mut matches = Vec::with_capacity(old.len().min(new.len()));
+    lcs_impl(old, new, 0, 0, &mut matches);
+    matches
+}
+
+fn lcs_impl<T: PartialEq>(
+    old: &[T],
+    new: &[T],
+    old_offset: usize,
+    new_offset: usize,
+    matches: &mut Vec<(usize, usize)>,
+) {
+    if old.is_empty() || new.is_empty() {
+        return;
+    }
+
+    let (distance, x, y, u, v) = middle_snake(old, new);
+
+    if distance <= 1 {
+        // One of the sequences is the other one with at most a single line inserted.
+        let (mut i, mut j) = (0, 0);
+        while i < old.len() && j < new.len() {
+            if old[i] == new[j] {
+                matches.push((old_offset + i, new_offset + j));
+                i += 1;
+                j += 1;
+            } else if old.len() > new.len() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        return;
+    }
+
+    lcs_impl(&old[..x], &new[..y], old_offset, new_offset, matches);
+    matches.extend((0..u - x).map(|step| (old_offset + x + step, new_offset + y + step)));
+    lcs_impl(&old[u..], &new[v..], old_offset + u, new_offset + v, matches);
+}
+
+// Returns the length of the shortest edit script and the snake `(x, y) -> (u, v)` in its middle.
+fn middle_snake<T: PartialEq>(old: &[T], new: &[T]) -> (usize, usize, usize, usize, usize) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    let mut forward = vec![0_isize; 2 * offset as usize + 1];
+    let mut backward = vec![0_isize; 2 * offset as usize + 1];
+    let at = |k: isize| (k + offset) as usize;
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let (sx, sy) = (x, y);
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+
+            let c = delta - k;
+            if odd && -d < c && c < d && forward[at(k)] + backward[at(c)] >= n {
+                return ((2 * d - 1) as usize, sx as usize, sy as usize, x as usize, y as usize);
+            }
+        }
+
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let (sx, sy) = (x, y);
+            while x < n && y < m && old[(n - 1 - x) as usize] == new[(m - 1 - y) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+
+            let c = delta - k;
+            if !odd && -d <= c && c <= d && backward[at(k)] + forward[at(c)] >= n {
+                return (
+                    (2 * d) as usize,
+                    (n - x) as usize,
+                    (m - y) as usize,
+                    (n - sx) as usize,
+                    (m - sy) as usize,
+                );
+            }
+        }
+    }
+
+    unreachable!("the shortest edit script is never longer than both sequences together")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lcs_len(old: &str, new: &str) -> usize {
+        let (old, new): (Vec<char>, Vec<char>) = (old.chars().collect(), new.chars().collect());
+        let matches = longest_common_subsequence(&old, &new);
+        assert!(matches.iter().all(|&(i, j)| old[i] == new[j]));
+        assert!(matches.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1));
+        matches.len()
+    }
+
+    #[test]
+    fn test_longest_common_subsequence() {
+        assert_eq!(lcs_len("", "abc"), 0);
+        assert_eq!(lcs_len("abc", "abc"), 3);
+        assert_eq!(lcs_len("ab", "a"), 1);
+        assert_eq!(lcs_len("abcabba", "cbabac"), 4);
+        assert_eq!(lcs_len("xaxbxcx", "abc"), 3);
+        assert_eq!(lcs_len("abcdef", "ghijkl"), 0);
+
+        // compare against the quadratic dynamic programming solution on pseudo random inputs
+        let mut seed = 42_u32;
+        let mut random = |len: u32| -> String {
+            (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    (b'a' + (seed >> 16) as u8 % 3) as char
+                })
+                .collect()
+        };
+        for round in 0..200 {
+            let (old, new) = (random(round % 13), random(round % 7 + 3));
+            let (a, b): (Vec<char>, Vec<char>) = (old.chars().collect(), new.chars().collect());
+            let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+            for i in (0..a.len()).rev() {
+                for j in (0..b.len()).rev() {
+                    table[i][j] = if a[i] == b[j] {
+                        table[i + 1][j + 1] + 1
+                    } else {
+                        table[i + 1][j].max(table[i][j + 1])
+                    };
+                }
+            }
+            assert_eq!(lcs_len(&old, &new), table[0][0], "{} {}", old, new);
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let lines = |lines: &[(LineKind, &str)]| {
+            lines
+                .iter()
+                .map(|(kind, line)| (*kind, line.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let old = lines(&[
+            (LineKind::Code, "fn a() {}"),
+            (LineKind::Comment, "// b"),
+            (LineKind::Code, "fn c() {}"),
+        ]);
+        let new = lines(&[
+            (LineKind::Code, "fn a() {}"),
+            (LineKind::Comment, "// B"),
+            (LineKind::Blank, ""),
+            (LineKind::Code, "fn d() {}"),
+            (LineKind::Code, "fn e() {}"),
+        ]);
+
+        let mut diff = DiffDetail::new("Rust");
+        diff.compare(Detail::new("Rust", 1, 0, 1, 1, 3), &old, &new);
+
+        assert_eq!((diff.same.blank, diff.same.comment, diff.same.code), (0, 0, 1));
+        assert_eq!(
+            (diff.modified.blank, diff.modified.comment, diff.modified.code),
+            (0, 1, 1)
+        );
+        assert_eq!((diff.added.blank, diff.added.comment, diff.added.code), (1, 0, 1));
+        assert_eq!((diff.removed.blank, diff.removed.comment, diff.removed.code), (0, 0, 0));
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+
+/// How the bytes of a source file were turned into text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    // assumed for whatever is not valid UTF-8, every byte decodes to some character
+    Windows1252,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Windows1252 => "Windows-1252",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// The characters of 0x80..=0x9F, where Windows-1252 differs from Latin-1, undefined bytes map to themselves.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}', '\u{02C6}',
+    '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{8D}', '\u{017D}', '\u{8F}', '\u{90}', '\u{2018}', '\u{2019}',
+    '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}', '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}',
+    '\u{0153}', '\u{9D}', '\u{017E}', '\u{0178}',
+];
+
+/// Decodes a whole file: UTF-16 when it starts with a byte order mark, UTF-8 when valid, Windows-1252 otherwise.
+pub fn decode(mut bytes: Vec<u8>) -> (String, Encoding) {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        bytes.drain(..3);
+    }
+
+    match bytes.as_slice() {
+        [0xFF, 0xFE, rest @ ..] => (decode_utf16(rest, u16::from_le_bytes), Encoding::Utf16Le),
+        [0xFE, 0xFF, rest @ ..] => (decode_utf16(rest, u16::from_be_bytes), Encoding::Utf16Be),
+        _ => match String::from_utf8(bytes) {
+            Ok(text) => (text, Encoding::Utf8),
+            Err(err) => (decode_windows_1252(err.as_bytes()), Encoding::Windows1252),
+        },
+    }
+}
+
+/// How many leading bytes of a file `looks_binary` needs to see.
+pub const SNIFF_SIZE: u64 = 8192;
+
+/// Text has no NUL bytes, except for the UTF-16 files recognized by their byte order mark.
+pub fn looks_binary(head: &[u8]) -> bool {
+    !(head.starts_with(&[0xFF, 0xFE]) || head.starts_with(&[0xFE, 0xFF])) && head.contains(&0)
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| unit([pair[0], pair[1]]))
+        .collect::<Vec<_>>();
+    String::from_utf16_lossy(&units)
+}
+
+fn decode_windows_1252(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| match byte {
+            0x80..=0x9F => WINDOWS_1252_HIGH[(byte - 0x80) as usize],
+            _ => byte as char,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            decode(b"fn main() {}".to_vec()),
+            ("fn main() {}".to_string(), Encoding::Utf8)
+        );
+        assert_eq!(
+            decode(b"\xEF\xBB\xBFx = 1".to_vec()),
+            ("x = 1".to_string(), Encoding::Utf8)
+        );
+        assert_eq!(
+            decode(b"\xFF\xFEa\0\n\0".to_vec()),
+            ("a\n".to_string(), Encoding::Utf16Le)
+        );
+        assert_eq!(
+            decode(b"\xFE\xFF\0a\0\n".to_vec()),
+            ("a\n".to_string(), Encoding::Utf16Be)
+        );
+        assert_eq!(
+            decode(b"caf\xe9 \x80 \x96".to_vec()),
+            ("café € –".to_string(), Encoding::Windows1252)
+        );
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(looks_binary(b"\x7fELF\x02\x01\x01\0\0"));
+        assert!(!looks_binary(b"\xFF\xFEa\0"));
+        assert!(!looks_binary("int café;".as_bytes()));
+        assert!(!looks_binary(b""));
+    }
+}
@@ -1,14 +1,20 @@
-use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::sync_channel;
 use std::thread::spawn;
 
-use crate::calculator::Calculator;
-use crate::explorer::Explorer;
-use crate::reporter::{Report, Reporter};
+use crate::calculator::{Calculator, Counted, Detail, FileDetail};
+use crate::encoding::{decode, looks_binary, SNIFF_SIZE};
+use crate::error::Error;
+use crate::explorer::{Explorer, WalkOptions};
+use crate::ignore::IgnoreFile;
+use crate::reporter::{Granularity, Report, Reporter, Skipped};
 use crate::spinner::Spinner;
+use crate::vcs::tracked_files;
+use crate::Result;
 
 pub struct Engine {
-    entry: PathBuf,
+    entries: Vec<PathBuf>,
 
     explorer: Explorer,
     calculator: Calculator,
@@ -16,39 +22,141 @@ pub struct Engine {
 }
 
 impl Engine {
-    pub fn new(entry: PathBuf, ignore_file: Option<PathBuf>) -> Self {
-        let ignore_list = Self::read_ignore_list(ignore_file);
+    pub fn new(entries: Vec<PathBuf>, walk: WalkOptions, granularity: Granularity) -> Result<Self> {
+        let entries = Self::outermost(entries);
+        let root = Self::common_root(&entries);
+        let ignore_file = match &walk.ignore_file {
+            Some(path) => Some(Self::read_ignore_file(path, &root)?),
+            None => None,
+        };
+        let tracked_files = match walk.vcs {
+            Some(vcs) => {
+                let mut files = vec![];
+                for entry in &entries {
+                    files.extend(tracked_files(vcs, entry)?);
+                }
+                Some(files)
+            }
+            None => None,
+        };
 
-        let (explorer, filename_receiver) = Explorer::new(ignore_list);
-        let (calculator, detail_receiver) = Calculator::new(filename_receiver);
-        let reporter = Reporter::new(detail_receiver);
+        let (explorer, filename_receiver) = Explorer::new(root.clone(), ignore_file, tracked_files, &walk);
+        let (calculator, detail_receiver) =
+            Calculator::new(filename_receiver, walk.filter, walk.max_file_size, walk.report_unknown);
+        let reporter = Reporter::new(detail_receiver, root, granularity);
 
-        Self {
-            entry,
+        Ok(Self {
+            entries,
             explorer,
             calculator,
             reporter,
-        }
+        })
     }
 
     pub fn serve(self) -> Report {
         #[rustfmt::skip]
-        let Self { entry, mut explorer, calculator, reporter } = self;
+        let Self { entries, mut explorer, calculator, reporter } = self;
         let spinner = Spinner::new();
 
         spinner.start();
-        spawn(move || explorer.walk_directory(entry));
-        spawn(|| calculator.calculate());
-        let report = reporter.research();
+        let walker = spawn(move || explorer.walk(entries));
+        let calculating = spawn(|| calculator.calculate());
+        let mut report = reporter.research();
+        let (unmatched_ignores, skipped, failures) = walker.join().unwrap_or_default();
+        let (too_large, unknown) = calculating.join().unwrap_or_default();
+        report.unmatched_ignores = unmatched_ignores;
+        report.skipped = Skipped { too_large, ..skipped };
+        report.unknown = unknown;
+        report.failures.extend(failures);
         spinner.stop();
 
         report
     }
 
-    #[rustfmt::skip]
-    fn read_ignore_list(filename: Option<PathBuf>) -> Option<Vec<PathBuf>> {
-        filename
-            .and_then(|filename| fs::read_to_string(filename).ok())
-            .map(|content| content.lines().map(|line| line.trim_start_matches("/")).filter_map(|path| fs::canonicalize(path).ok()).collect())
+    /// Counts the source read from stdin as a single file, named after `name` in the report.
+    pub fn serve_stdin(language: Option<String>, name: Option<PathBuf>, granularity: Granularity) -> Result<Report> {
+        let mut bytes = vec![];
+        io::stdin().read_to_end(&mut bytes)?;
+        let (sender, receiver) = sync_channel::<Counted>(1);
+        let path = name.clone().unwrap_or_else(|| PathBuf::from("-"));
+
+        // Skipped and sized like a file on disk: by its raw bytes, whatever they decode to.
+        if looks_binary(&bytes[..bytes.len().min(SNIFF_SIZE as usize)]) {
+            let _ = sender.send(Counted::Binary(path));
+        } else {
+            let size = bytes.len() as u64;
+            let (content, encoding) = decode(bytes);
+            let detail = Detail {
+                bytes: size,
+                ..Calculator::content_detail(&content, language.as_deref(), name.as_deref())?
+            };
+            let _ = sender.send(Counted::File(FileDetail { path, detail, encoding }));
+        }
+        drop(sender);
+
+        Ok(Reporter::new(receiver, PathBuf::new(), granularity).research())
+    }
+
+    // Reported paths are relative to this directory: the entry itself, or the directory holding it.
+    pub fn root_of(entry: &Path) -> PathBuf {
+        if entry.is_dir() {
+            entry.to_path_buf()
+        } else {
+            entry.parent().map(PathBuf::from).unwrap_or_default()
+        }
+    }
+
+    /// The deepest directory holding all of the entries, the root of a single entry.
+    pub fn common_root(entries: &[PathBuf]) -> PathBuf {
+        let mut roots = entries.iter().map(|entry| Self::root_of(entry));
+        let first = roots.next().unwrap_or_default();
+        roots.fold(first, |common, root| {
+            common
+                .ancestors()
+                .find(|dir| root.starts_with(dir))
+                .map(PathBuf::from)
+                .unwrap_or_default()
+        })
+    }
+
+    // Entries inside another entry, or given twice, would be counted twice.
+    // Sorted by components, whatever lies inside an entry directly follows it.
+    fn outermost(mut entries: Vec<PathBuf>) -> Vec<PathBuf> {
+        entries.sort();
+        let mut kept = Vec::<PathBuf>::with_capacity(entries.len());
+        for entry in entries {
+            if !kept.last().is_some_and(|outer| entry.starts_with(outer)) {
+                kept.push(entry);
+            }
+        }
+        kept
+    }
+
+    // Patterns of the ignore file are relative to the counted tree, wherever rcloc runs from.
+    fn read_ignore_file(filename: &Path, root: &Path) -> Result<IgnoreFile> {
+        IgnoreFile::load(filename, root)
+            .map_err(|err| Error::InvalidArg(format!("--ignore-file {}: {}", filename.display(), err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries() {
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let entries = vec![
+            manifest.join("src"),
+            manifest.join("Cargo.toml"),
+            manifest.join("src/main.rs"),
+        ];
+
+        assert_eq!(Engine::common_root(&entries), manifest);
+        assert_eq!(Engine::common_root(&entries[..1]), manifest.join("src"));
+        assert_eq!(
+            Engine::outermost(entries),
+            vec![manifest.join("Cargo.toml"), manifest.join("src")]
+        );
     }
 }
@@ -2,7 +2,11 @@
 pub enum Error {
     Io(std::io::Error),
 
+    Json(serde_json::Error),
+
     InvalidArg(String),
+
+    Vcs(String),
 }
 
 impl std::error::Error for Error {}
@@ -13,11 +17,19 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Io(err) => write!(f, "{}", err),
+            Error::Json(err) => write!(f, "malformed report: {}", err),
             Error::InvalidArg(s) => write!(f, "invalid argument: {}", s),
+            Error::Vcs(s) => write!(f, "{}", s),
         }
     }
 }
@@ -1,46 +1,370 @@
-use std::fs;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs::{self, Metadata};
+use std::io;
+use std::mem;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 
+use crate::calculator::FileError;
+use crate::filter::Filter;
+use crate::ignore::{IgnoreFile, Ignorer};
+use crate::options::Vcs;
+use crate::reporter::Skipped;
+
+/// Decides which files below the entry get counted, and whether the others of no known language are tallied.
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    pub ignore_file: Option<PathBuf>,
+    pub vcs: Option<Vcs>,
+    pub no_ignore: bool,
+    pub follow_symlinks: bool,
+    pub one_file_system: bool,
+    pub max_depth: Option<usize>,
+    pub skip_hidden: bool,
+    pub max_file_size: Option<u64>,
+    pub report_unknown: bool,
+    pub filter: Filter,
+}
+
+// What tells two paths leading to the same directory apart.
+#[cfg(unix)]
+type FileId = (u64, u64);
+#[cfg(not(unix))]
+type FileId = PathBuf;
+
+#[cfg(unix)]
+fn file_id(_: &Path, metadata: &Metadata) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(path: &Path, _: &Metadata) -> Option<FileId> {
+    fs::canonicalize(path).ok()
+}
+
+#[cfg(unix)]
+fn device(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+// Mount points are not told apart elsewhere, so every directory is on the same file system.
+#[cfg(not(unix))]
+fn device(_: &Metadata) -> Option<u64> {
+    None
+}
+
+// Hidden wins over --max-depth, as with --vcs where a tracked file below both counts as hidden.
+#[derive(Debug, Clone, Copy)]
+enum Pruned {
+    Hidden,
+    TooDeep,
+}
+
 pub struct Explorer {
-    ignore_list: Option<Vec<PathBuf>>,
+    // the directory the filter sees paths relative to
+    root: PathBuf,
+    ignore_file: Option<IgnoreFile>,
+    tracked_files: Option<Vec<PathBuf>>,
+    // whether the ignore files found in the tree apply, each entry path gets its own Ignorer
+    use_ignorer: bool,
+    ignorer: Option<Ignorer>,
+    follow_symlinks: bool,
+    // the directories walked so far, only kept when following symlinks as they may lead in circles
+    visited: HashSet<FileId>,
+    // with --one-file-system, the device of the entry being walked
+    device: Option<u64>,
+    one_file_system: bool,
+    max_depth: Option<usize>,
+    skip_hidden: bool,
+    skipped: Skipped,
+    // why the directory being walked is left out, its files are then only counted in `skipped`
+    pruned: Option<Pruned>,
+    // the directories and entries that could not be read, relative to the root
+    failures: Vec<FileError>,
+    filter: Filter,
     sender: SyncSender<PathBuf>,
 }
 
 impl Explorer {
-    pub fn new(ignore_list: Option<Vec<PathBuf>>) -> (Self, Receiver<PathBuf>) {
+    pub fn new(
+        root: PathBuf,
+        ignore_file: Option<IgnoreFile>,
+        tracked_files: Option<Vec<PathBuf>>,
+        walk: &WalkOptions,
+    ) -> (Self, Receiver<PathBuf>) {
         let (sender, receiver) = sync_channel::<PathBuf>(1024);
-        let explorer = Self { ignore_list, sender };
+        let explorer = Self {
+            root,
+            ignore_file,
+            tracked_files,
+            use_ignorer: !walk.no_ignore,
+            ignorer: None,
+            follow_symlinks: walk.follow_symlinks,
+            visited: HashSet::new(),
+            device: None,
+            one_file_system: walk.one_file_system,
+            max_depth: walk.max_depth,
+            skip_hidden: walk.skip_hidden,
+            skipped: Skipped::default(),
+            pruned: None,
+            failures: vec![],
+            filter: walk.filter.clone(),
+            sender,
+        };
         (explorer, receiver)
     }
 
-    pub fn walk_directory<P: AsRef<Path>>(&mut self, entry: P) {
-        self.walk_dir_impl(entry.as_ref());
+    /// Sends every file to count below any of the entries, returns the patterns of the ignore file that never
+    /// matched anything, what was left out by --no-hidden and --max-depth, and what could not be read.
+    pub fn walk(&mut self, entries: Vec<PathBuf>) -> (Vec<String>, Skipped, Vec<FileError>) {
+        match self.tracked_files.take() {
+            // Tracked files that were deleted from the working tree are no files anymore.
+            Some(files) => {
+                for path in files {
+                    let is_file = self
+                        .metadata(&path)
+                        .is_ok_and(|metadata| metadata.is_some_and(|metadata| metadata.is_file()));
+                    if !is_file || self.is_excluded(&path) || !self.accepts_file(&path) {
+                        continue;
+                    }
+                    let relative = path.strip_prefix(&self.root).unwrap_or(&path);
+                    let mut directories = relative.ancestors().skip(1);
+                    if !directories.all(|dir| dir.as_os_str().is_empty() || self.filter.accepts_dir(dir)) {
+                        continue;
+                    }
+                    if self.skip_hidden
+                        && relative
+                            .components()
+                            .any(|component| Self::is_hidden(component.as_ref()))
+                    {
+                        self.skipped.hidden += 1;
+                        continue;
+                    }
+                    if self.max_depth.is_some_and(|max| relative.components().count() > max) {
+                        self.skipped.too_deep += 1;
+                        continue;
+                    }
+                    let _ = self.sender.send(path);
+                }
+            }
+            None => {
+                for entry in entries {
+                    if entry.is_file() {
+                        if !self.is_excluded(&entry) {
+                            self.walk_dir_impl(&entry, 0);
+                        }
+                    } else {
+                        self.ignorer = if self.use_ignorer {
+                            Some(Ignorer::new(&entry))
+                        } else {
+                            None
+                        };
+                        if let Ok(metadata) = entry.metadata() {
+                            self.device = device(&metadata).filter(|_| self.one_file_system);
+                            if self.is_first_visit(&entry, &metadata) {
+                                self.walk_dir_impl(&entry, 0);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let unmatched_ignores = self
+            .ignore_file
+            .as_ref()
+            .map(|ignore_file| ignore_file.unused_patterns())
+            .unwrap_or_default();
+        (unmatched_ignores, self.skipped, std::mem::take(&mut self.failures))
     }
 
-    fn walk_dir_impl(&mut self, path: &Path) {
-        if path.is_file() && self.is_not_ignore_file(path) {
-            let _ = self.sender.send(PathBuf::from(path));
-        } else if path.is_dir() {
-            if let Ok(entries) = fs::read_dir(path) {
-                entries
-                    .filter_map(|entry| entry.ok())
-                    .for_each(|entry| self.walk_dir_impl(&entry.path()));
+    // `depth` counts the directories between the entry and `path`, the entry itself being at 0.
+    fn walk_dir_impl(&mut self, dir: &Path, depth: usize) {
+        if dir.is_file() {
+            if self.accepts_file(dir) {
+                match self.pruned {
+                    None => {
+                        let _ = self.sender.send(PathBuf::from(dir));
+                    }
+                    Some(Pruned::Hidden) => self.skipped.hidden += 1,
+                    Some(Pruned::TooDeep) => self.skipped.too_deep += 1,
+                }
+            }
+        } else if dir.is_dir() {
+            let outer = self.pruned;
+            if outer.is_none() && self.max_depth.is_some_and(|max| depth >= max) {
+                self.pruned = Some(Pruned::TooDeep);
+            }
+            let entered = self.ignorer.as_mut().map_or(0, |ignorer| ignorer.enter(dir));
+            match fs::read_dir(dir) {
+                Ok(entries) => {
+                    let mut paths = vec![];
+                    for entry in entries {
+                        let path = match entry {
+                            Ok(entry) => entry.path(),
+                            Err(error) => {
+                                self.fail(dir, error);
+                                continue;
+                            }
+                        };
+                        let metadata = match self.metadata(&path) {
+                            Ok(Some(metadata)) => metadata,
+                            Ok(None) => continue,
+                            Err(error) => {
+                                if !self.is_ignored(&path) {
+                                    self.fail(&path, error);
+                                }
+                                continue;
+                            }
+                        };
+                        if self.is_ignored(&path) {
+                            continue;
+                        }
+                        if metadata.is_dir() && !(self.accepts_dir(&path) && self.is_first_visit(&path, &metadata)) {
+                            continue;
+                        }
+                        let hidden = self.skip_hidden && path.file_name().is_some_and(Self::is_hidden);
+                        paths.push((path, if hidden { Some(Pruned::Hidden) } else { self.pruned }));
+                    }
+                    for (path, pruned) in paths {
+                        let parent = mem::replace(&mut self.pruned, pruned);
+                        self.walk_dir_impl(&path, depth + 1);
+                        self.pruned = parent;
+                    }
+                }
+                Err(error) => self.fail(dir, error),
+            }
+            if let Some(ignorer) = self.ignorer.as_mut() {
+                ignorer.leave(entered);
             }
+            self.pruned = outer;
         }
     }
 
+    // Symlinks are only walked through with --follow-symlinks, `None` when left alone, an error when broken.
+    fn metadata(&self, path: &Path) -> io::Result<Option<Metadata>> {
+        let metadata = fs::symlink_metadata(path)?;
+        match (metadata.file_type().is_symlink(), self.follow_symlinks) {
+            (false, _) => Ok(Some(metadata)),
+            (true, true) => fs::metadata(path).map(Some),
+            (true, false) => Ok(None),
+        }
+    }
+
+    fn fail(&mut self, path: &Path, error: io::Error) {
+        self.failures.push(FileError {
+            path: path.strip_prefix(&self.root).unwrap_or(path).to_path_buf(),
+            error: error.into(),
+        });
+    }
+
+    // Whether a directory is to be walked: on the entry's file system, and not reached before through a symlink.
+    fn is_first_visit(&mut self, dir: &Path, metadata: &Metadata) -> bool {
+        if self.device.is_some() && device(metadata) != self.device {
+            return false;
+        }
+        if !self.follow_symlinks {
+            return true;
+        }
+        match file_id(dir, metadata) {
+            Some(id) => self.visited.insert(id),
+            None => true,
+        }
+    }
+
+    #[inline]
+    fn is_hidden(name: &OsStr) -> bool {
+        name.to_string_lossy().starts_with('.')
+    }
+
+    #[inline]
+    fn accepts_dir(&self, dir: &Path) -> bool {
+        self.filter.accepts_dir(dir.strip_prefix(&self.root).unwrap_or(dir))
+    }
+
+    #[inline]
+    fn accepts_file(&self, file: &Path) -> bool {
+        self.filter.accepts_file(file.strip_prefix(&self.root).unwrap_or(file))
+    }
+
+    // The patterns of --ignore-file take precedence over the ignore files found in the tree.
     #[inline]
-    fn is_not_ignore_file<P: AsRef<Path>>(&self, filename: P) -> bool {
-        self.is_not_ignore_file_impl(filename.as_ref())
+    fn is_ignored(&self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        let decision = self
+            .ignore_file
+            .as_ref()
+            .and_then(|ignore_file| ignore_file.matched(path, is_dir));
+
+        match (decision, &self.ignorer) {
+            (Some(ignored), _) => ignored,
+            (None, Some(ignorer)) => ignorer.is_ignored(path, is_dir),
+            (None, None) => false,
+        }
     }
 
+    // Files sent without walking down to them, the entry itself or the files tracked by a vcs.
     #[inline]
-    fn is_not_ignore_file_impl(&self, filename: &Path) -> bool {
-        if let Some(ignore_list) = &self.ignore_list {
-            return ignore_list.iter().all(|path| !filename.starts_with(path));
+    fn is_excluded(&self, filename: &Path) -> bool {
+        match &self.ignore_file {
+            Some(ignore_file) => ignore_file.excludes(filename),
+            None => false,
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop() {
+        use std::os::unix::fs::symlink;
+
+        let root = env::temp_dir().join(format!("rcloc-explorer-{}", process::id()));
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("a/b/main.rs"), "fn main() {}\n").unwrap();
+        symlink("../..", root.join("a/b/up")).unwrap();
+        symlink("a", root.join("again")).unwrap();
+
+        let walk = WalkOptions {
+            follow_symlinks: true,
+            no_ignore: true,
+            ..WalkOptions::default()
+        };
+        let (mut explorer, receiver) = Explorer::new(root.clone(), None, None, &walk);
+        let (_, _, failures) = explorer.walk(vec![root.clone()]);
+        drop(explorer);
+        let files = receiver.iter().collect::<Vec<_>>();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(failures.is_empty());
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn test_is_first_visit() {
+        let dir = env::temp_dir();
+        let metadata = fs::metadata(&dir).unwrap();
+        let walk = WalkOptions::default();
+
+        let (mut explorer, _receiver) = Explorer::new(dir.clone(), None, None, &walk);
+        assert!(explorer.is_first_visit(&dir, &metadata));
+        assert!(explorer.is_first_visit(&dir, &metadata));
+
+        explorer.follow_symlinks = true;
+        assert!(explorer.is_first_visit(&dir, &metadata));
+        assert!(!explorer.is_first_visit(&dir, &metadata));
 
-        true
+        // with --one-file-system, a directory on another device than the entry's
+        explorer.follow_symlinks = false;
+        explorer.device = device(&metadata).map(|device| device.wrapping_add(1));
+        assert_eq!(explorer.is_first_visit(&dir, &metadata), explorer.device.is_none());
     }
 }
@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::calculator::find_language;
+use crate::error::Error;
+use crate::options::Options;
+use crate::util::slash_path;
+use crate::Result;
+
+/// Restricts the counted files by language, extension, name and directory, empty lists restrict nothing.
+///
+/// Paths handed to the filter are relative to the counted tree.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    include_languages: HashSet<&'static str>,
+    exclude_languages: HashSet<&'static str>,
+    include_extensions: HashSet<String>,
+    exclude_extensions: HashSet<String>,
+    exclude_dirs: HashSet<OsString>,
+    match_file: Option<Regex>,
+    not_match_file: Option<Regex>,
+    match_dir: Option<Regex>,
+    not_match_dir: Option<Regex>,
+}
+
+impl Filter {
+    pub fn new(options: &Options) -> Result<Self> {
+        let languages = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| {
+                    find_language(name).ok_or_else(|| Error::InvalidArg(format!("unknown language `{}`", name)))
+                })
+                .collect::<Result<HashSet<_>>>()
+        };
+        // `.rs` and `rs` both name the extension of `main.rs`
+        let extensions = |exts: &[String]| exts.iter().map(|ext| ext.trim_start_matches('.').to_string()).collect();
+        let regex = |option: &str, pattern: &Option<String>| {
+            pattern
+                .as_ref()
+                .map(|pattern| Regex::new(pattern).map_err(|err| Error::InvalidArg(format!("--{}: {}", option, err))))
+                .transpose()
+        };
+
+        Ok(Self {
+            include_languages: languages(&options.include_lang)?,
+            exclude_languages: languages(&options.exclude_lang)?,
+            include_extensions: extensions(&options.include_ext),
+            exclude_extensions: extensions(&options.exclude_ext),
+            exclude_dirs: options.exclude_dir.iter().map(OsString::from).collect(),
+            match_file: regex("match-f", &options.match_f)?,
+            not_match_file: regex("not-match-f", &options.not_match_f)?,
+            match_dir: regex("match-d", &options.match_d)?,
+            not_match_dir: regex("not-match-d", &options.not_match_d)?,
+        })
+    }
+
+    // Rejected directories are not walked at all.
+    pub fn accepts_dir(&self, dir: &Path) -> bool {
+        let excluded = dir.file_name().is_some_and(|name| self.exclude_dirs.contains(name));
+        let not_matched = self
+            .not_match_dir
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(&slash_path(dir)));
+
+        !excluded && !not_matched
+    }
+
+    pub fn accepts_file(&self, file: &Path) -> bool {
+        let extension = file.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        let name = file.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        let dir = file.parent().map(slash_path).unwrap_or_default();
+
+        (self.include_extensions.is_empty() || self.include_extensions.contains(extension))
+            && !self.exclude_extensions.contains(extension)
+            && self.match_file.as_ref().is_none_or(|regex| regex.is_match(&name))
+            && !self.not_match_file.as_ref().is_some_and(|regex| regex.is_match(&name))
+            && self.match_dir.as_ref().is_none_or(|regex| regex.is_match(&dir))
+    }
+
+    pub fn accepts_language(&self, language: &str) -> bool {
+        (self.include_languages.is_empty() || self.include_languages.contains(language))
+            && !self.exclude_languages.contains(language)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use structopt::StructOpt;
+
+    use super::*;
+
+    fn filter(args: &[&str]) -> Result<Filter> {
+        Filter::new(&Options::from_iter(Some("rcloc").iter().chain(args)))
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = filter(&[
+            "--include-lang",
+            "rust,C",
+            "--exclude-ext",
+            ".json",
+            "--exclude-dir",
+            "node_modules",
+        ])
+        .unwrap();
+
+        assert!(filter.accepts_language("Rust"));
+        assert!(!filter.accepts_language("Go"));
+        assert!(filter.accepts_file(Path::new("src/main.rs")));
+        assert!(!filter.accepts_file(Path::new("package.json")));
+        assert!(!filter.accepts_dir(Path::new("web/node_modules")));
+        assert!(filter.accepts_dir(Path::new("web/src")));
+    }
+
+    #[test]
+    fn test_filter_regex() {
+        let filter = filter(&[
+            "--not-match-f",
+            "_test\\.go$",
+            "--match-d",
+            "(^|/)src(/|$)",
+            "--not-match-d",
+            "^vendor",
+        ])
+        .unwrap();
+
+        assert!(filter.accepts_file(Path::new("src/main.go")));
+        assert!(filter.accepts_file(Path::new("cmd/src/app/main.go")));
+        assert!(!filter.accepts_file(Path::new("src/main_test.go")));
+        assert!(!filter.accepts_file(Path::new("main.go")));
+        assert!(!filter.accepts_dir(Path::new("vendor/github.com")));
+        assert!(filter.accepts_dir(Path::new("cmd/vendor")));
+    }
+
+    #[test]
+    fn test_filter_errors() {
+        assert!(filter(&["--exclude-lang", "Klingon"]).is_err());
+        assert!(filter(&["--match-f", "("]).is_err());
+    }
+}
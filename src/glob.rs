@@ -0,0 +1,158 @@
+/// A shell style pattern with the gitignore flavour of wildcards: `*` and `?` stop at `/`, `**` between slashes
+/// spans any number of directories, `[a-z]` and `[!a-z]` match character classes and `\` escapes the next one.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Literal(char),
+    AnyChar,
+    Star,
+    // a trailing `**`, matches everything including slashes
+    DoubleStar,
+    // `**/`, matches nothing or any number of leading directories
+    AnyDirs,
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        let chars = pattern.chars().collect::<Vec<_>>();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '\\' if i + 1 < chars.len() => {
+                    tokens.push(Token::Literal(chars[i + 1]));
+                    i += 1;
+                }
+                '*' => {
+                    let mut end = i;
+                    while end < chars.len() && chars[end] == '*' {
+                        end += 1;
+                    }
+                    let whole_component = (i == 0 || chars[i - 1] == '/') && (end == chars.len() || chars[end] == '/');
+                    if end - i >= 2 && whole_component {
+                        if end == chars.len() {
+                            tokens.push(Token::DoubleStar);
+                        } else {
+                            tokens.push(Token::AnyDirs);
+                            end += 1;
+                        }
+                    } else {
+                        tokens.push(Token::Star);
+                    }
+                    i = end;
+                    continue;
+                }
+                '?' => tokens.push(Token::AnyChar),
+                '[' => match Self::parse_class(&chars[i + 1..]) {
+                    Some((token, len)) => {
+                        tokens.push(token);
+                        i += len;
+                    }
+                    None => tokens.push(Token::Literal('[')),
+                },
+                c => tokens.push(Token::Literal(c)),
+            }
+            i += 1;
+        }
+
+        Self { tokens }
+    }
+
+    // Parses the class following a `[`, returns it with the number of characters it spans up to the closing `]`.
+    fn parse_class(chars: &[char]) -> Option<(Token, usize)> {
+        let negated = matches!(chars.first(), Some('!') | Some('^'));
+        let mut i = if negated { 1 } else { 0 };
+        let start = i;
+        let mut ranges = Vec::new();
+
+        while i < chars.len() {
+            let c = chars[i];
+            if c == ']' && i > start {
+                return Some((Token::Class { negated, ranges }, i + 1));
+            }
+            if i + 2 < chars.len() && chars[i + 1] == '-' && chars[i + 2] != ']' {
+                ranges.push((c, chars[i + 2]));
+                i += 3;
+            } else {
+                ranges.push((c, c));
+                i += 1;
+            }
+        }
+
+        None
+    }
+
+    /// Whether `path`, with `/` separated components, matches the whole pattern.
+    pub fn is_match(&self, path: &str) -> bool {
+        let text = path.chars().collect::<Vec<_>>();
+        Self::match_impl(&self.tokens, &text)
+    }
+
+    fn match_impl(tokens: &[Token], text: &[char]) -> bool {
+        let (token, rest) = match tokens.split_first() {
+            Some(split) => split,
+            None => return text.is_empty(),
+        };
+
+        match token {
+            Token::Literal(c) => text.first() == Some(c) && Self::match_impl(rest, &text[1..]),
+            Token::AnyChar => matches!(text.first(), Some(&c) if c != '/') && Self::match_impl(rest, &text[1..]),
+            Token::Class { negated, ranges } => match text.first() {
+                Some(&c) if c != '/' => {
+                    let inside = ranges.iter().any(|&(low, high)| low <= c && c <= high);
+                    inside != *negated && Self::match_impl(rest, &text[1..])
+                }
+                _ => false,
+            },
+            Token::Star => {
+                for skip in 0..=text.len() {
+                    if Self::match_impl(rest, &text[skip..]) {
+                        return true;
+                    }
+                    if skip < text.len() && text[skip] == '/' {
+                        break;
+                    }
+                }
+                false
+            }
+            Token::DoubleStar => (0..=text.len()).any(|skip| Self::match_impl(rest, &text[skip..])),
+            Token::AnyDirs => {
+                Self::match_impl(rest, text)
+                    || (0..text.len()).any(|slash| text[slash] == '/' && Self::match_impl(rest, &text[slash + 1..]))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_match() {
+        assert!(Glob::new("*.rs").is_match("main.rs"));
+        assert!(!Glob::new("*.rs").is_match("src/main.rs"));
+        assert!(Glob::new("src/*.rs").is_match("src/main.rs"));
+        assert!(Glob::new("?.c").is_match("a.c"));
+        assert!(!Glob::new("?.c").is_match("ab.c"));
+        assert!(Glob::new("[a-c]x").is_match("bx"));
+        assert!(!Glob::new("[!a-c]x").is_match("bx"));
+        assert!(Glob::new("\\*.md").is_match("*.md"));
+        assert!(!Glob::new("\\*.md").is_match("a.md"));
+
+        assert!(Glob::new("**/target").is_match("target"));
+        assert!(Glob::new("**/target").is_match("a/b/target"));
+        assert!(Glob::new("a/**/b").is_match("a/b"));
+        assert!(Glob::new("a/**/b").is_match("a/x/y/b"));
+        assert!(!Glob::new("a/**/b").is_match("a/xb"));
+        assert!(Glob::new("vendor/**").is_match("vendor/x/y.go"));
+        assert!(Glob::new("a**b").is_match("axxb"));
+        assert!(!Glob::new("a**b").is_match("a/b"));
+    }
+}
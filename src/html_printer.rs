@@ -0,0 +1,334 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::baseline::Delta;
+use crate::calculator::Detail;
+use crate::differ::DiffReport;
+use crate::reporter::{DirectoryDetail, Metadata, Report, UnknownFiles};
+use crate::util::{bytes_to_size, escape_markup};
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2em; color: #24292e; }
+h1 { font-size: 1.5em; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 0.2em 1em; }
+dt { font-weight: bold; }
+dd { margin: 0; }
+table { border-collapse: collapse; min-width: 60em; }
+th, td { padding: 0.3em 0.8em; border-bottom: 1px solid #e1e4e8; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+thead th { cursor: pointer; user-select: none; background: #f6f8fa; }
+thead th.asc::after { content: " \25B2"; }
+thead th.desc::after { content: " \25BC"; }
+tfoot td { font-weight: bold; }
+td:last-child { width: 16em; }
+.bar { background: #e1e4e8; height: 0.8em; position: relative; }
+.bar span { background: #2f81f7; display: block; height: 100%; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll("thead th").forEach(function (th) {
+    var column = th.cellIndex;
+    th.addEventListener("click", function () {
+        var tbody = th.closest("table").tBodies[0];
+        var asc = !th.classList.contains("asc");
+        th.parentNode.querySelectorAll("th").forEach(function (other) { other.classList.remove("asc", "desc"); });
+        th.classList.add(asc ? "asc" : "desc");
+        Array.from(tbody.rows)
+            .sort(function (a, b) {
+                var x = a.cells[column].dataset.value, y = b.cells[column].dataset.value;
+                var order = isNaN(x) ? x.localeCompare(y) : x - y;
+                return asc ? order : -order;
+            })
+            .forEach(function (row) { tbody.appendChild(row); });
+    });
+});
+"#;
+
+pub fn html_print(report: Report, metadata: Metadata) {
+    let Report {
+        sections,
+        files,
+        directories,
+        summary,
+        sources,
+        baseline,
+        unknown,
+        ..
+    } = report;
+    let Metadata { entry, elapsed } = metadata;
+
+    println!("<!DOCTYPE html>");
+    println!("<html lang=\"en\">");
+    println!("<head>");
+    println!("<meta charset=\"utf-8\">");
+    println!(
+        "<title>rcloc report - {}</title>",
+        escape_markup(&entry.to_string_lossy())
+    );
+    println!("<style>{}</style>", STYLE);
+    println!("</head>");
+    println!("<body>");
+    println!("<h1>rcloc report</h1>");
+    println!("<dl>");
+    println!("<dt>entry</dt><dd>{}</dd>", escape_markup(&entry.to_string_lossy()));
+    println!("<dt>version</dt><dd>{}</dd>", env!("CARGO_PKG_VERSION"));
+    println!("<dt>elapsed</dt><dd>{:.4} secs</dd>", elapsed.as_secs_f64());
+    if let Some(baseline) = &baseline {
+        println!(
+            "<dt>baseline</dt><dd>{}</dd>",
+            escape_markup(&baseline.path.to_string_lossy())
+        );
+    }
+    println!("</dl>");
+    println!("<table>");
+
+    match (files, directories, sources, baseline) {
+        (Some(files), _, _, _) => {
+            println!("<thead><tr><th>File</th><th>Language</th><th>Encoding</th><th>size</th><th>blank</th><th>comment</th><th>code</th><th>share of code</th></tr></thead>");
+            println!("<tbody>");
+            for file in &files {
+                let path = escape_markup(&file.path.to_string_lossy());
+                let language = escape_markup(file.detail.language);
+                let encoding = file.encoding.to_string();
+                println!(
+                    "<tr>{}{}{}{}</tr>",
+                    cell(&path, &path),
+                    cell(&language, &language),
+                    cell(&encoding, &encoding),
+                    numbers(&file.detail, summary.code)
+                );
+            }
+            println!("</tbody>");
+            println!(
+                "<tfoot><tr>{}{}{}{}</tr></tfoot>",
+                cell("Sum", "Sum"),
+                cell("", ""),
+                cell("", ""),
+                numbers(&summary, summary.code)
+            );
+        }
+        (None, Some(directories), _, _) => html_print_directories(directories, summary, "Directory"),
+        (None, None, Some(sources), _) => html_print_directories(sources, summary, "Source"),
+        (None, None, None, Some(baseline)) => {
+            println!("<thead><tr><th>Language</th><th>files</th><th>size</th><th>blank</th><th>comment</th><th>code</th><th>&Delta; code</th><th>&Delta; code %</th><th>share of code</th></tr></thead>");
+            println!("<tbody>");
+            for detail in &sections {
+                println!(
+                    "<tr>{}</tr>",
+                    baseline_cells(detail, &baseline.previous(detail.language), summary.code)
+                );
+            }
+            println!("</tbody>");
+            println!(
+                "<tfoot><tr>{}</tr></tfoot>",
+                baseline_cells(&summary, &baseline.summary, summary.code)
+            );
+        }
+        (None, None, None, None) => {
+            println!("<thead><tr><th>Language</th><th>files</th><th>size</th><th>blank</th><th>comment</th><th>code</th><th>share of code</th></tr></thead>");
+            println!("<tbody>");
+            for detail in &sections {
+                println!("{}", row(detail, summary.code));
+            }
+            println!("</tbody>");
+            println!("<tfoot>{}</tfoot>", row(&summary, summary.code));
+        }
+    }
+
+    println!("</table>");
+    if let Some(unknown) = unknown {
+        html_print_unknown(unknown);
+    }
+    println!("<script>{}</script>", SCRIPT);
+    println!("</body>");
+    println!("</html>");
+}
+
+fn html_print_unknown(unknown: Vec<UnknownFiles>) {
+    println!("<h2>Files of no known language</h2>");
+    println!("<table>");
+    println!("<thead><tr><th>Unknown</th><th>files</th><th>size</th></tr></thead>");
+    println!("<tbody>");
+    for files in &unknown {
+        let name = escape_markup(&files.name);
+        println!(
+            "<tr>{}{}{}</tr>",
+            cell(&name, &name),
+            cell(files.files, files.files),
+            cell(files.bytes, bytes_to_size(files.bytes as f64))
+        );
+    }
+    println!("</tbody>");
+    println!("</table>");
+}
+
+fn html_print_directories(directories: Vec<DirectoryDetail>, summary: Detail, heading: &str) {
+    let breakdown = directories.iter().any(|directory| !directory.languages.is_empty());
+    if breakdown {
+        println!("<thead><tr><th>{}</th><th>Language</th><th>files</th><th>size</th><th>blank</th><th>comment</th><th>code</th><th>share of code</th></tr></thead>", heading);
+    } else {
+        println!("<thead><tr><th>{}</th><th>files</th><th>size</th><th>blank</th><th>comment</th><th>code</th><th>share of code</th></tr></thead>", heading);
+    }
+    println!("<tbody>");
+    for directory in &directories {
+        let path = escape_markup(&directory.path.to_string_lossy());
+        if breakdown {
+            for detail in &directory.languages {
+                println!("<tr>{}{}</tr>", cell(&path, &path), cells(detail, summary.code));
+            }
+        } else {
+            let detail = &directory.total;
+            println!(
+                "<tr>{}{}{}</tr>",
+                cell(&path, &path),
+                cell(detail.files, detail.files),
+                numbers(detail, summary.code)
+            );
+        }
+    }
+    println!("</tbody>");
+    if breakdown {
+        println!(
+            "<tfoot><tr>{}{}</tr></tfoot>",
+            cell("Sum", "Sum"),
+            cells(
+                &Detail {
+                    language: "",
+                    ..summary
+                },
+                summary.code
+            )
+        );
+    } else {
+        println!("<tfoot>{}</tfoot>", row(&summary, summary.code));
+    }
+}
+
+pub fn html_print_diff(report: DiffReport, elapsed: Duration) {
+    let DiffReport {
+        old,
+        new,
+        sections,
+        summary,
+        ..
+    } = report;
+
+    println!("<!DOCTYPE html>");
+    println!("<html lang=\"en\">");
+    println!("<head>");
+    println!("<meta charset=\"utf-8\">");
+    println!("<title>rcloc diff - {}</title>", escape_markup(&new.to_string_lossy()));
+    println!("<style>{}</style>", STYLE);
+    println!("</head>");
+    println!("<body>");
+    println!("<h1>rcloc diff</h1>");
+    println!("<dl>");
+    println!("<dt>old</dt><dd>{}</dd>", escape_markup(&old.to_string_lossy()));
+    println!("<dt>new</dt><dd>{}</dd>", escape_markup(&new.to_string_lossy()));
+    println!("<dt>version</dt><dd>{}</dd>", env!("CARGO_PKG_VERSION"));
+    println!("<dt>elapsed</dt><dd>{:.4} secs</dd>", elapsed.as_secs_f64());
+    println!("</dl>");
+    println!("<table>");
+    println!("<thead><tr><th>Language</th><th>status</th><th>files</th><th>blank</th><th>comment</th><th>code</th></tr></thead>");
+    println!("<tbody>");
+    for diff in &sections {
+        let language = escape_markup(diff.language);
+        for (status, detail) in diff.rows().iter() {
+            println!(
+                "<tr>{}{}{}</tr>",
+                cell(&language, &language),
+                cell(status, status),
+                delta_cells(detail)
+            );
+        }
+    }
+    println!("</tbody>");
+    println!("<tfoot>");
+    for (status, detail) in summary.rows().iter() {
+        println!(
+            "<tr>{}{}{}</tr>",
+            cell("Sum", "Sum"),
+            cell(status, status),
+            delta_cells(detail)
+        );
+    }
+    println!("</tfoot>");
+    println!("</table>");
+    println!("<script>{}</script>", SCRIPT);
+    println!("</body>");
+    println!("</html>");
+}
+
+fn baseline_cells(detail: &Detail, previous: &Detail, total_code: usize) -> String {
+    let language = escape_markup(detail.language);
+    let delta = Delta::new(previous, detail);
+    let (code, percent) = delta.code_columns();
+
+    format!(
+        "{}{}{}{}{}{}",
+        cell(&language, &language),
+        cell(detail.files, detail.files),
+        counts(detail),
+        cell(delta.code, code),
+        cell(delta.code_percent.unwrap_or_default(), percent),
+        share(detail, total_code),
+    )
+}
+
+fn delta_cells(detail: &Detail) -> String {
+    format!(
+        "{}{}{}{}",
+        cell(detail.files, detail.files),
+        cell(detail.blank, detail.blank),
+        cell(detail.comment, detail.comment),
+        cell(detail.code, detail.code),
+    )
+}
+
+fn row(detail: &Detail, total_code: usize) -> String {
+    format!("<tr>{}</tr>", cells(detail, total_code))
+}
+
+fn cells(detail: &Detail, total_code: usize) -> String {
+    let language = escape_markup(detail.language);
+    format!(
+        "{}{}{}",
+        cell(&language, &language),
+        cell(detail.files, detail.files),
+        numbers(detail, total_code),
+    )
+}
+
+fn numbers(detail: &Detail, total_code: usize) -> String {
+    format!("{}{}", counts(detail), share(detail, total_code))
+}
+
+fn counts(detail: &Detail) -> String {
+    format!(
+        "{}{}{}{}",
+        cell(detail.bytes, bytes_to_size(detail.bytes as f64)),
+        cell(detail.blank, detail.blank),
+        cell(detail.comment, detail.comment),
+        cell(detail.code, detail.code),
+    )
+}
+
+// The share of code is always the last column, the stylesheet reserves room for its bar.
+fn share(detail: &Detail, total_code: usize) -> String {
+    let share = if total_code == 0 {
+        0_f64
+    } else {
+        detail.code as f64 * 100_f64 / total_code as f64
+    };
+    let bar = format!(
+        "<div class=\"bar\" title=\"{0:.2}%\"><span style=\"width: {0:.2}%\"></span></div>",
+        share
+    );
+
+    cell(format!("{:.2}", share), bar)
+}
+
+// `data-value` carries the raw number the sort script compares, the cell text is what gets displayed.
+fn cell<V: Display, T: Display>(value: V, text: T) -> String {
+    format!("<td data-value=\"{}\">{}</td>", value, text)
+}
@@ -0,0 +1,252 @@
+use std::cell::Cell;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use lazy_static::lazy_static;
+
+use crate::glob::Glob;
+use crate::util::slash_path;
+
+// One line of an ignore file.
+#[derive(Debug)]
+struct Rule {
+    pattern: String,
+    glob: Glob,
+    negated: bool,
+    dir_only: bool,
+    // Patterns with a slash match the path relative to the ignore file, the others only the file name.
+    anchored: bool,
+    // set once the rule matched a path, even when a later rule overrode it
+    used: Cell<bool>,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let original = line.trim_end_matches('\r');
+        if original.is_empty() || original.starts_with('#') {
+            return None;
+        }
+
+        // A leading `\#` or `\!` is left to the glob, which reads it as an escaped character.
+        let (negated, line) = match original.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, original),
+        };
+
+        // Trailing spaces are ignored unless escaped with a backslash.
+        let mut pattern = line.trim_end_matches(' ');
+        if pattern.ends_with('\\') && line.len() > pattern.len() {
+            pattern = &line[..pattern.len() + 1];
+        }
+
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        let anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            pattern: original.to_string(),
+            glob: Glob::new(pattern),
+            negated,
+            dir_only,
+            anchored,
+            used: Cell::new(false),
+        })
+    }
+}
+
+/// The rules of one `.gitignore` like file, relative to the directory `base`.
+#[derive(Debug)]
+pub struct IgnoreFile {
+    base: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl IgnoreFile {
+    pub fn load(path: &Path, base: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(Self::parse(&content, base))
+    }
+
+    fn read(path: &Path, base: &Path) -> Option<Self> {
+        Self::load(path, base).ok()
+    }
+
+    fn parse(content: &str, base: &Path) -> Self {
+        Self {
+            base: base.to_path_buf(),
+            rules: content.lines().filter_map(Rule::parse).collect(),
+        }
+    }
+
+    /// `Some(true)` when the last matching rule ignores `path`, `Some(false)` when it is re-included by a negation.
+    pub fn matched(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = slash_path(path.strip_prefix(&self.base).ok()?);
+        let name = relative.rsplit('/').next().unwrap_or_default();
+
+        let mut decision = None;
+        for rule in self.rules.iter().filter(|rule| is_dir || !rule.dir_only) {
+            if rule.glob.is_match(if rule.anchored { &relative } else { name }) {
+                rule.used.set(true);
+                decision = Some(!rule.negated);
+            }
+        }
+        decision
+    }
+
+    /// Like `matched` for a path that was not reached by walking down from `base`, so its directories count too.
+    pub fn excludes(&self, path: &Path) -> bool {
+        let directories = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| *dir != self.base && dir.starts_with(&self.base));
+        let mut directories = directories.collect::<Vec<_>>();
+        directories.reverse();
+
+        directories.iter().any(|dir| self.matched(dir, true) == Some(true)) || self.matched(path, false) == Some(true)
+    }
+
+    /// Lines of the file whose pattern did not match any path so far, as written.
+    pub fn unused_patterns(&self) -> Vec<String> {
+        self.rules
+            .iter()
+            .filter(|rule| !rule.used.get())
+            .map(|rule| rule.pattern.clone())
+            .collect()
+    }
+}
+
+/// Ignore rules in effect while walking a tree: `.gitignore` and `.ignore` files of the directories
+/// entered so far, then `.git/info/exclude` and git's `core.excludesFile` of the enclosing repository.
+#[derive(Debug)]
+pub struct Ignorer {
+    // innermost last, a `.ignore` after the `.gitignore` of the same directory
+    nested: Vec<IgnoreFile>,
+    global: Vec<IgnoreFile>,
+}
+
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+lazy_static! {
+    // asked once, every entry path gets an Ignorer of its own
+    static ref EXCLUDES_FILE: Option<PathBuf> = Ignorer::excludes_file();
+}
+
+impl Ignorer {
+    pub fn new(entry: &Path) -> Self {
+        let mut ignorer = Self {
+            nested: vec![],
+            global: vec![],
+        };
+
+        if let Some(repository) = entry.ancestors().find(|dir| dir.join(".git").exists()) {
+            ignorer
+                .global
+                .extend(IgnoreFile::read(&repository.join(".git/info/exclude"), repository));
+            if let Some(excludes_file) = EXCLUDES_FILE.as_ref() {
+                ignorer.global.extend(IgnoreFile::read(excludes_file, repository));
+            }
+
+            // Ignore files between the repository and the entry apply to the entry as well.
+            let mut ancestors = entry
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| dir.starts_with(repository))
+                .collect::<Vec<_>>();
+            ancestors.reverse();
+            for dir in ancestors {
+                ignorer.enter(dir);
+            }
+        }
+
+        ignorer
+    }
+
+    // git's `core.excludesFile`, which defaults to `$XDG_CONFIG_HOME/git/ignore`.
+    fn excludes_file() -> Option<PathBuf> {
+        let configured = Command::new("git")
+            .args(["config", "--path", "--get", "core.excludesFile"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|path| !path.is_empty());
+
+        match configured {
+            Some(path) => Some(PathBuf::from(path)),
+            None => env::var_os("XDG_CONFIG_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+                .map(|config| config.join("git/ignore")),
+        }
+    }
+
+    /// Picks up the ignore files of `dir`, returns how many were found so that `leave` can drop them again.
+    pub fn enter(&mut self, dir: &Path) -> usize {
+        let before = self.nested.len();
+        for name in IGNORE_FILES.iter() {
+            self.nested.extend(IgnoreFile::read(&dir.join(name), dir));
+        }
+        self.nested.len() - before
+    }
+
+    pub fn leave(&mut self, count: usize) {
+        self.nested.truncate(self.nested.len() - count);
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if is_dir && path.file_name() == Some(".git".as_ref()) {
+            return true;
+        }
+
+        self.nested
+            .iter()
+            .rev()
+            .chain(self.global.iter())
+            .find_map(|file| file.matched(path, is_dir))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matched() {
+        let content = "# build output\ntarget/\n*.log\n!keep.log\n/docs/*.html\nsrc/**/generated\n\\#notes\n";
+        let file = IgnoreFile::parse(content, Path::new("/repo"));
+        let matched = |path: &str, is_dir: bool| file.matched(Path::new(path), is_dir);
+
+        assert_eq!(matched("/repo/target", true), Some(true));
+        assert_eq!(matched("/repo/crate/target", true), Some(true));
+        assert_eq!(matched("/repo/target", false), None);
+        assert_eq!(matched("/repo/a/debug.log", false), Some(true));
+        assert_eq!(matched("/repo/a/keep.log", false), Some(false));
+        assert_eq!(matched("/repo/docs/index.html", false), Some(true));
+        assert_eq!(matched("/repo/a/docs/index.html", false), None);
+        assert_eq!(matched("/repo/src/a/b/generated", true), Some(true));
+        assert_eq!(matched("/repo/#notes", false), Some(true));
+        assert_eq!(matched("/elsewhere/debug.log", false), None);
+
+        assert!(file.excludes(Path::new("/repo/crate/target/debug/main.rs")));
+        assert!(!file.excludes(Path::new("/repo/src/main.rs")));
+        assert_eq!(file.unused_patterns(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_unused_patterns() {
+        let file = IgnoreFile::parse("*.tmp\n!keep.tmp\n!never.txt\nout/\n", Path::new("/repo"));
+
+        assert_eq!(file.matched(Path::new("/repo/keep.tmp"), false), Some(false));
+        assert_eq!(file.unused_patterns(), vec!["!never.txt", "out/"]);
+    }
+}
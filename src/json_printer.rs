@@ -0,0 +1,306 @@
+use std::io::stdout;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::baseline::Delta;
+use crate::calculator::{Detail, FileDetail};
+use crate::differ::{DiffDetail, DiffReport};
+use crate::reporter::{DirectoryDetail, Metadata, Report};
+
+/// Bumped whenever a field of the JSON document is renamed or removed, see README for the layout.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema: u32,
+    version: &'a str,
+    entry: &'a Path,
+    elapsed: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline: Option<&'a Path>,
+    sections: Vec<JsonDetail<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<JsonFileDetail<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    directories: Option<Vec<JsonDirectoryDetail<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sources: Option<Vec<JsonDirectoryDetail<'a>>>,
+    summary: JsonDetail<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<JsonSkipped>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown: Option<Vec<JsonUnknownFiles<'a>>>,
+}
+
+#[derive(Serialize)]
+struct JsonUnknownFiles<'a> {
+    name: &'a str,
+    files: usize,
+    bytes: u64,
+}
+
+#[derive(Serialize)]
+struct JsonSkipped {
+    hidden: usize,
+    too_deep: usize,
+    too_large: usize,
+    binary: usize,
+}
+
+#[derive(Serialize)]
+struct JsonDetail<'a> {
+    language: &'a str,
+    files: usize,
+    bytes: u64,
+    blank: usize,
+    comment: usize,
+    code: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delta: Option<JsonChange>,
+}
+
+impl<'a> From<&'a Detail> for JsonDetail<'a> {
+    fn from(detail: &'a Detail) -> Self {
+        #[rustfmt::skip]
+        let Detail { language, files, bytes, blank, comment, code } = *detail;
+        Self {
+            language,
+            files,
+            bytes,
+            blank,
+            comment,
+            code,
+            delta: None,
+        }
+    }
+}
+
+impl<'a> JsonDetail<'a> {
+    fn compared_to(detail: &'a Detail, previous: Option<Detail>) -> Self {
+        let delta = previous.map(|previous| {
+            #[rustfmt::skip]
+            let Delta { files, bytes, blank, comment, code, code_percent } = Delta::new(&previous, detail);
+            JsonChange {
+                files,
+                bytes,
+                blank,
+                comment,
+                code,
+                code_percent,
+            }
+        });
+        Self {
+            delta,
+            ..Self::from(detail)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonChange {
+    files: i64,
+    bytes: i64,
+    blank: i64,
+    comment: i64,
+    code: i64,
+    code_percent: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct JsonFileDetail<'a> {
+    path: &'a Path,
+    language: &'a str,
+    encoding: String,
+    bytes: u64,
+    blank: usize,
+    comment: usize,
+    code: usize,
+}
+
+impl<'a> From<&'a FileDetail> for JsonFileDetail<'a> {
+    fn from(file: &'a FileDetail) -> Self {
+        #[rustfmt::skip]
+        let Detail { language, bytes, blank, comment, code, .. } = file.detail;
+        Self {
+            path: &file.path,
+            language,
+            encoding: file.encoding.to_string(),
+            bytes,
+            blank,
+            comment,
+            code,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDirectoryDetail<'a> {
+    path: &'a Path,
+    files: usize,
+    bytes: u64,
+    blank: usize,
+    comment: usize,
+    code: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    languages: Vec<JsonDetail<'a>>,
+}
+
+impl<'a> From<&'a DirectoryDetail> for JsonDirectoryDetail<'a> {
+    fn from(directory: &'a DirectoryDetail) -> Self {
+        #[rustfmt::skip]
+        let Detail { files, bytes, blank, comment, code, .. } = directory.total;
+        let languages = directory.languages.iter().map(JsonDetail::from).collect();
+        Self {
+            path: &directory.path,
+            files,
+            bytes,
+            blank,
+            comment,
+            code,
+            languages,
+        }
+    }
+}
+
+pub fn json_print(report: Report, metadata: Metadata) {
+    let Report {
+        sections,
+        files,
+        directories,
+        summary,
+        sources,
+        baseline,
+        skipped,
+        binaries,
+        unknown,
+        ..
+    } = report;
+    let Metadata { entry, elapsed } = metadata;
+
+    let previous = |language| baseline.as_ref().map(|baseline| baseline.previous(language));
+
+    let document = JsonReport {
+        schema: SCHEMA_VERSION,
+        version: env!("CARGO_PKG_VERSION"),
+        entry: &entry,
+        elapsed: elapsed.as_secs_f64(),
+        baseline: baseline.as_ref().map(|baseline| baseline.path.as_path()),
+        sections: sections
+            .iter()
+            .map(|detail| JsonDetail::compared_to(detail, previous(detail.language)))
+            .collect(),
+        files: files
+            .as_ref()
+            .map(|files| files.iter().map(JsonFileDetail::from).collect()),
+        directories: directories
+            .as_ref()
+            .map(|directories| directories.iter().map(JsonDirectoryDetail::from).collect()),
+        sources: sources
+            .as_ref()
+            .map(|sources| sources.iter().map(JsonDirectoryDetail::from).collect()),
+        summary: JsonDetail::compared_to(&summary, baseline.as_ref().map(|baseline| baseline.summary)),
+        skipped: if skipped.is_empty() && binaries.is_empty() {
+            None
+        } else {
+            Some(JsonSkipped {
+                hidden: skipped.hidden,
+                too_deep: skipped.too_deep,
+                too_large: skipped.too_large,
+                binary: binaries.len(),
+            })
+        },
+        unknown: unknown.as_ref().map(|unknown| {
+            unknown
+                .iter()
+                .map(|files| JsonUnknownFiles {
+                    name: &files.name,
+                    files: files.files,
+                    bytes: files.bytes,
+                })
+                .collect()
+        }),
+    };
+
+    if serde_json::to_writer_pretty(stdout(), &document).is_ok() {
+        println!();
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDiffReport<'a> {
+    schema: u32,
+    version: &'a str,
+    old: &'a Path,
+    new: &'a Path,
+    elapsed: f64,
+    sections: Vec<JsonDiffDetail<'a>>,
+    summary: JsonDiffDetail<'a>,
+}
+
+#[derive(Serialize)]
+struct JsonDiffDetail<'a> {
+    language: &'a str,
+    same: JsonDelta,
+    modified: JsonDelta,
+    added: JsonDelta,
+    removed: JsonDelta,
+}
+
+#[derive(Serialize)]
+struct JsonDelta {
+    files: usize,
+    blank: usize,
+    comment: usize,
+    code: usize,
+}
+
+impl From<&Detail> for JsonDelta {
+    fn from(detail: &Detail) -> Self {
+        #[rustfmt::skip]
+        let Detail { files, blank, comment, code, .. } = *detail;
+        Self {
+            files,
+            blank,
+            comment,
+            code,
+        }
+    }
+}
+
+impl<'a> From<&'a DiffDetail> for JsonDiffDetail<'a> {
+    fn from(diff: &'a DiffDetail) -> Self {
+        Self {
+            language: diff.language,
+            same: JsonDelta::from(&diff.same),
+            modified: JsonDelta::from(&diff.modified),
+            added: JsonDelta::from(&diff.added),
+            removed: JsonDelta::from(&diff.removed),
+        }
+    }
+}
+
+pub fn json_print_diff(report: DiffReport, elapsed: Duration) {
+    let DiffReport {
+        old,
+        new,
+        sections,
+        summary,
+        ..
+    } = report;
+
+    let document = JsonDiffReport {
+        schema: SCHEMA_VERSION,
+        version: env!("CARGO_PKG_VERSION"),
+        old: &old,
+        new: &new,
+        elapsed: elapsed.as_secs_f64(),
+        sections: sections.iter().map(JsonDiffDetail::from).collect(),
+        summary: JsonDiffDetail::from(&summary),
+    };
+
+    if serde_json::to_writer_pretty(stdout(), &document).is_ok() {
+        println!();
+    }
+}
@@ -1,24 +1,49 @@
+mod baseline;
 mod calculator;
+mod cloc_printer;
+mod csv_printer;
+mod differ;
+mod encoding;
 mod engine;
 mod error;
 mod executor;
 mod explorer;
+mod filter;
+mod glob;
+mod html_printer;
+mod ignore;
+mod json_printer;
+mod markdown_printer;
 mod options;
 mod pretty_printer;
 mod reporter;
 mod spinner;
 mod util;
+mod vcs;
 
 use std::env::current_dir;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
 use std::time::Instant;
 
 use structopt::StructOpt;
 
+use crate::baseline::Baseline;
+use crate::calculator::{Detail, FileError};
+use crate::cloc_printer::{xml_print, xml_print_diff, yaml_print, yaml_print_diff};
+use crate::csv_printer::{csv_print, csv_print_diff};
+use crate::differ::Differ;
 use crate::engine::Engine;
-use crate::options::{Options, SortBy};
-use crate::pretty_printer::pretty_print;
-use crate::util::compare;
+use crate::explorer::WalkOptions;
+use crate::filter::Filter;
+use crate::html_printer::{html_print, html_print_diff};
+use crate::json_printer::{json_print, json_print_diff};
+use crate::markdown_printer::{markdown_print, markdown_print_diff};
+use crate::options::{Command, Options, Output};
+use crate::pretty_printer::{pretty_print, pretty_print_diff};
+use crate::reporter::{Granularity, Metadata, Report, Skipped};
+use crate::util::{compare, compare_detail, read_path_list};
 
 type Result<T> = std::result::Result<T, crate::error::Error>;
 
@@ -27,27 +52,301 @@ static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 fn main() {
     let now = Instant::now();
+    let mut options = Options::from_args();
 
+    match options.command.take() {
+        Some(Command::Diff { old, new }) => diff(options, old, new, now),
+        Some(Command::SumReports { reports, by_source }) => sum_reports(options, reports, by_source, now),
+        None => count(options, now),
+    }
+}
+
+// Options deciding which files get counted, shared by counting and diffing.
+fn walk_options(options: &Options) -> WalkOptions {
+    let filter = Filter::new(options).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+
+    WalkOptions {
+        ignore_file: options.ignore_file.clone(),
+        vcs: options.vcs,
+        no_ignore: options.no_ignore,
+        follow_symlinks: options.follow_symlinks,
+        one_file_system: options.one_file_system,
+        max_depth: options.max_depth,
+        skip_hidden: options.no_hidden && !options.hidden,
+        max_file_size: options.max_file_size.map(|size| size.0),
+        report_unknown: options.report_unknown,
+        filter,
+    }
+}
+
+fn count(options: Options, now: Instant) {
+    let walk = walk_options(&options);
     #[rustfmt::skip]
-    let Options { sort_by, order_by, entry, ignore_file, .. } = Options::from_args();
+    let Options { output, sort_by, order_by, entries, files_from, ignore_file, stdin_lang, stdin_name, verbose, strict, by_file, by_directory, depth, breakdown, baseline, no_sum, .. } = options;
 
-    let entry = entry.and_then(|entry| fs::canonicalize(entry).ok()).unwrap_or_else(|| {
-        eprintln!("No directory specified, so use current directory as entry.\n");
-        current_dir().expect("current directory does not exist")
+    let granularity = if by_file {
+        Granularity::File
+    } else if by_directory {
+        Granularity::Directory {
+            depth: depth.0,
+            breakdown,
+        }
+    } else {
+        Granularity::Language
+    };
+
+    let baseline = baseline.map(|path| {
+        Baseline::load(&path).unwrap_or_else(|err| {
+            eprintln!("{}: {}", path.display(), err);
+            process::exit(1);
+        })
     });
 
-    let machine = Engine::new(entry, ignore_file);
-    let mut report = machine.serve();
-    report.sections.sort_by(|prev, next| match sort_by {
-        SortBy::Language => compare(prev.language, next.language, order_by),
-        SortBy::Files => compare(prev.files, next.files, order_by),
-        SortBy::Size => compare(prev.bytes, next.bytes, order_by),
-        SortBy::Blank => compare(prev.blank, next.blank, order_by),
-        SortBy::Comment => compare(prev.comment, next.comment, order_by),
-        SortBy::Code => compare(prev.code, next.code, order_by),
+    let (mut report, entry) = if stdin_lang.is_some() || stdin_name.is_some() {
+        let report = Engine::serve_stdin(stdin_lang, stdin_name, granularity).unwrap_or_else(|err| {
+            eprintln!("stdin: {}", err);
+            process::exit(1);
+        });
+        (report, current_dir().expect("current directory does not exist"))
+    } else {
+        let entries = resolve_entries(entries, files_from);
+        // The JSON, YAML and HTML reports name the single entry, or the directory holding all of them.
+        let entry = match entries.as_slice() {
+            [] => current_dir().expect("current directory does not exist"),
+            [entry] => entry.clone(),
+            entries => Engine::common_root(entries),
+        };
+
+        let machine = Engine::new(entries, walk, granularity).unwrap_or_else(|err| {
+            eprintln!("{}: {}", entry.display(), err);
+            process::exit(1);
+        });
+        let report = machine.serve();
+        print_unmatched_ignores(ignore_file.as_deref(), &report.unmatched_ignores);
+        print_skipped(report.skipped);
+        (report, entry)
+    };
+    print_binaries(&report.binaries, verbose);
+    let failed = report.failures.len();
+    print_failures(&report.failures, verbose);
+    if let Some(baseline) = baseline {
+        // Languages that disappeared since the baseline still get a row, with all counts at zero.
+        for &language in baseline.sections.keys() {
+            if report.sections.iter().all(|detail| detail.language != language) {
+                report.sections.push(Detail::new(language, 0, 0, 0, 0, 0));
+            }
+        }
+        report.baseline = Some(baseline);
+    }
+    report
+        .sections
+        .sort_by(|prev, next| compare_detail(prev, next, sort_by, order_by));
+    if let Some(files) = &mut report.files {
+        files.sort_by(|prev, next| {
+            compare_detail(&prev.detail, &next.detail, sort_by, order_by).then_with(|| prev.path.cmp(&next.path))
+        });
+    }
+    if let Some(directories) = &mut report.directories {
+        directories.sort_by(|prev, next| {
+            compare_detail(&prev.total, &next.total, sort_by, order_by).then_with(|| prev.path.cmp(&next.path))
+        });
+        for directory in directories.iter_mut() {
+            directory
+                .languages
+                .sort_by(|prev, next| compare_detail(prev, next, sort_by, order_by));
+        }
+    }
+
+    let elapsed = now.elapsed();
+    let metadata = Metadata { entry, elapsed };
+
+    print(report, metadata, output, no_sum);
+    if strict && failed > 0 {
+        process::exit(1);
+    }
+}
+
+// The positional paths and those listed in --files-from, canonicalized, the current directory when there are none.
+fn resolve_entries(mut entries: Vec<PathBuf>, files_from: Option<PathBuf>) -> Vec<PathBuf> {
+    if let Some(files_from) = files_from {
+        let listed = read_path_list(&files_from).unwrap_or_else(|err| {
+            eprintln!("{}: {}", files_from.display(), err);
+            process::exit(1);
+        });
+        entries.extend(listed);
+    } else if entries.is_empty() {
+        eprintln!("No directory specified, so use current directory as entry.\n");
+        entries.push(current_dir().expect("current directory does not exist"));
+    }
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            fs::canonicalize(&entry).unwrap_or_else(|err| {
+                eprintln!("{}: {}", entry.display(), err);
+                process::exit(1);
+            })
+        })
+        .collect()
+}
+
+fn sum_reports(options: Options, reports: Vec<PathBuf>, by_source: bool, now: Instant) {
+    let Options {
+        output,
+        sort_by,
+        order_by,
+        no_sum,
+        ..
+    } = options;
+
+    let saved = reports
+        .into_iter()
+        .map(|path| {
+            Baseline::load(&path).unwrap_or_else(|err| {
+                eprintln!("{}: {}", path.display(), err);
+                process::exit(1);
+            })
+        })
+        .collect();
+
+    let mut report = Report::merge(saved, by_source);
+    report
+        .sections
+        .sort_by(|prev, next| compare_detail(prev, next, sort_by, order_by));
+    if let Some(sources) = &mut report.sources {
+        for source in sources.iter_mut() {
+            source
+                .languages
+                .sort_by(|prev, next| compare_detail(prev, next, sort_by, order_by));
+        }
+    }
+
+    let elapsed = now.elapsed();
+    let entry = current_dir().expect("current directory does not exist");
+
+    print(report, Metadata { entry, elapsed }, output, no_sum);
+}
+
+fn print(report: Report, metadata: Metadata, output: Output, no_sum: bool) {
+    let elapsed = metadata.elapsed;
+
+    match output {
+        Output::Terminal => pretty_print(report, elapsed),
+        Output::Markdown => markdown_print(report),
+        Output::Json => json_print(report, metadata),
+        Output::Html => html_print(report, metadata),
+        Output::Yaml => yaml_print(report, metadata),
+        Output::Xml => xml_print(report, metadata),
+        Output::Csv => csv_print(report, ',', !no_sum),
+        Output::Tsv => csv_print(report, '\t', !no_sum),
+    }
+}
+
+fn diff(options: Options, old: PathBuf, new: PathBuf, now: Instant) {
+    let walk = walk_options(&options);
+    let Options {
+        output,
+        order_by,
+        ignore_file,
+        verbose,
+        strict,
+        no_sum,
+        ..
+    } = options;
+
+    let canonicalize = |path: PathBuf| {
+        fs::canonicalize(&path).unwrap_or_else(|err| {
+            eprintln!("{}: {}", path.display(), err);
+            process::exit(1);
+        })
+    };
+
+    let differ = Differ::new(canonicalize(old), canonicalize(new), walk);
+    let mut report = differ.diff().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
     });
+    report
+        .sections
+        .sort_by(|prev, next| compare(prev.language, next.language, order_by));
+    print_unmatched_ignores(ignore_file.as_deref(), &report.unmatched_ignores);
+    print_binaries(&report.binaries, verbose);
+    print_skipped(report.skipped);
+    let failed = report.failures.len();
+    print_failures(&report.failures, verbose);
 
     let elapsed = now.elapsed();
 
-    pretty_print(report, elapsed);
+    match output {
+        Output::Terminal => pretty_print_diff(report, elapsed),
+        Output::Markdown => markdown_print_diff(report),
+        Output::Json => json_print_diff(report, elapsed),
+        Output::Html => html_print_diff(report, elapsed),
+        Output::Yaml => yaml_print_diff(report, elapsed),
+        Output::Xml => xml_print_diff(report, elapsed),
+        Output::Csv => csv_print_diff(report, ',', !no_sum),
+        Output::Tsv => csv_print_diff(report, '\t', !no_sum),
+    }
+    if strict && failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn print_unmatched_ignores(ignore_file: Option<&Path>, patterns: &[String]) {
+    if let Some(ignore_file) = ignore_file {
+        for pattern in patterns {
+            eprintln!(
+                "{}: pattern `{}` did not match anything",
+                ignore_file.display(),
+                pattern
+            );
+        }
+    }
+}
+
+// Binary files are listed like the failures, with --verbose.
+fn print_binaries(binaries: &[PathBuf], verbose: bool) {
+    if binaries.is_empty() {
+        return;
+    }
+    if verbose {
+        for path in binaries {
+            eprintln!("{}: binary content", path.display());
+        }
+        eprintln!("skipped {} binary files", binaries.len());
+    } else {
+        eprintln!("skipped {} binary files, --verbose lists them", binaries.len());
+    }
+}
+
+fn print_skipped(skipped: Skipped) {
+    #[rustfmt::skip]
+    let Skipped { hidden, too_deep, too_large } = skipped;
+    if too_large > 0 {
+        eprintln!("skipped {} files larger than --max-file-size", too_large);
+    }
+    if hidden > 0 {
+        eprintln!("skipped {} hidden files", hidden);
+    }
+    if too_deep > 0 {
+        eprintln!("skipped {} files below --max-depth", too_deep);
+    }
+}
+
+// Only the count goes to stderr unless --verbose asks for every file and its reason.
+fn print_failures(failures: &[FileError], verbose: bool) {
+    if failures.is_empty() {
+        return;
+    }
+    if verbose {
+        for failure in failures {
+            eprintln!("{}: {}", failure.path.display(), failure.error);
+        }
+        eprintln!("{} files could not be read", failures.len());
+    } else {
+        eprintln!("{} files could not be read, --verbose lists them", failures.len());
+    }
 }
@@ -0,0 +1,183 @@
+use crate::baseline::{Baseline, Delta};
+use crate::calculator::{Detail, FileDetail};
+use crate::differ::DiffReport;
+use crate::reporter::{DirectoryDetail, Report, UnknownFiles};
+use crate::util::bytes_to_size;
+
+pub fn markdown_print(mut report: Report) {
+    let unknown = report.unknown.take();
+    markdown_print_report(report);
+    if let Some(unknown) = unknown {
+        markdown_print_unknown(unknown);
+    }
+}
+
+fn markdown_print_report(report: Report) {
+    let Report {
+        sections,
+        files,
+        directories,
+        summary,
+        sources,
+        baseline,
+        ..
+    } = report;
+
+    if let Some(files) = files {
+        return markdown_print_files(files, summary);
+    }
+    if let Some(directories) = directories {
+        return markdown_print_directories(directories, summary, "Directory");
+    }
+    if let Some(sources) = sources {
+        return markdown_print_directories(sources, summary, "Source");
+    }
+    if let Some(baseline) = baseline {
+        return markdown_print_baseline(sections, summary, baseline);
+    }
+
+    println!("| Language | files | size | blank | comment | code |");
+    println!("|:---------|------:|-----:|------:|--------:|-----:|");
+
+    for detail in sections {
+        println!(
+            "| {} | {} | {} | {} | {} | {} |",
+            detail.language,
+            detail.files,
+            bytes_to_size(detail.bytes as f64),
+            detail.blank,
+            detail.comment,
+            detail.code,
+        );
+    }
+
+    println!(
+        "| **Sum** | **{}** | **{}** | **{}** | **{}** | **{}** |",
+        summary.files,
+        bytes_to_size(summary.bytes as f64),
+        summary.blank,
+        summary.comment,
+        summary.code,
+    );
+}
+
+fn markdown_print_unknown(unknown: Vec<UnknownFiles>) {
+    println!();
+    println!("| Unknown | files | size |");
+    println!("|:--------|------:|-----:|");
+    for files in unknown {
+        println!(
+            "| {} | {} | {} |",
+            files.name,
+            files.files,
+            bytes_to_size(files.bytes as f64)
+        );
+    }
+}
+
+fn markdown_print_baseline(sections: Vec<Detail>, summary: Detail, baseline: Baseline) {
+    println!("| Language | files | size | blank | comment | code | Δ code | Δ code % |");
+    println!("|:---------|------:|-----:|------:|--------:|-----:|-------:|---------:|");
+
+    for detail in &sections {
+        let (code, percent) = Delta::new(&baseline.previous(detail.language), detail).code_columns();
+        println!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} |",
+            detail.language,
+            detail.files,
+            bytes_to_size(detail.bytes as f64),
+            detail.blank,
+            detail.comment,
+            detail.code,
+            code,
+            percent,
+        );
+    }
+
+    let (code, percent) = Delta::new(&baseline.summary, &summary).code_columns();
+    println!(
+        "| **Sum** | **{}** | **{}** | **{}** | **{}** | **{}** | **{}** | **{}** |",
+        summary.files,
+        bytes_to_size(summary.bytes as f64),
+        summary.blank,
+        summary.comment,
+        summary.code,
+        code,
+        percent,
+    );
+}
+
+fn markdown_print_files(files: Vec<FileDetail>, summary: Detail) {
+    println!("| File | Language | Encoding | blank | comment | code |");
+    println!("|:-----|:---------|:---------|------:|--------:|-----:|");
+
+    for file in files {
+        let FileDetail { path, detail, encoding } = file;
+        println!(
+            "| {} | {} | {} | {} | {} | {} |",
+            path.to_string_lossy().replace('|', "\\|"),
+            detail.language,
+            encoding,
+            detail.blank,
+            detail.comment,
+            detail.code,
+        );
+    }
+
+    println!(
+        "| **Sum** | | | **{}** | **{}** | **{}** |",
+        summary.blank, summary.comment, summary.code,
+    );
+}
+
+fn markdown_print_directories(directories: Vec<DirectoryDetail>, summary: Detail, heading: &str) {
+    let print_row = |name: &str, detail: &Detail| {
+        println!(
+            "| {} | {} | {} | {} | {} | {} |",
+            name,
+            detail.files,
+            bytes_to_size(detail.bytes as f64),
+            detail.blank,
+            detail.comment,
+            detail.code,
+        );
+    };
+
+    println!("| {} | files | size | blank | comment | code |", heading);
+    println!(
+        "|:{}|------:|-----:|------:|--------:|-----:|",
+        "-".repeat(heading.len() + 1)
+    );
+
+    for directory in directories {
+        print_row(&directory.path.to_string_lossy().replace('|', "\\|"), &directory.total);
+        for detail in &directory.languages {
+            print_row(&format!("&emsp;{}", detail.language), detail);
+        }
+    }
+
+    println!(
+        "| **Sum** | **{}** | **{}** | **{}** | **{}** | **{}** |",
+        summary.files,
+        bytes_to_size(summary.bytes as f64),
+        summary.blank,
+        summary.comment,
+        summary.code,
+    );
+}
+
+pub fn markdown_print_diff(report: DiffReport) {
+    let DiffReport { sections, summary, .. } = report;
+
+    println!("| Language | status | files | blank | comment | code |");
+    println!("|:---------|:-------|------:|------:|--------:|-----:|");
+
+    for diff in sections.iter().chain(Some(&summary)) {
+        for (status, detail) in diff.rows().iter() {
+            println!(
+                "| {} | {} | {} | {} | {} | {} |",
+                diff.language, status, detail.files, detail.blank, detail.comment, detail.code,
+            );
+        }
+    }
+}
@@ -9,6 +9,12 @@ use crate::error::Error;
 pub enum Output {
     Terminal,
     Markdown,
+    Json,
+    Html,
+    Yaml,
+    Xml,
+    Csv,
+    Tsv,
 }
 
 impl FromStr for Output {
@@ -19,12 +25,18 @@ impl FromStr for Output {
         match s.as_str() {
             "terminal" => Ok(Output::Terminal),
             "markdown" => Ok(Output::Markdown),
+            "json" => Ok(Output::Json),
+            "html" => Ok(Output::Html),
+            "yaml" => Ok(Output::Yaml),
+            "xml" => Ok(Output::Xml),
+            "csv" => Ok(Output::Csv),
+            "tsv" => Ok(Output::Tsv),
             _ => Err(Error::InvalidArg(s)),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum SortBy {
     Language,
     Files,
@@ -70,6 +82,64 @@ impl FromStr for OrderBy {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum Vcs {
+    Git,
+}
+
+impl FromStr for Vcs {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        match s.as_str() {
+            "git" => Ok(Vcs::Git),
+            _ => Err(Error::InvalidArg(s)),
+        }
+    }
+}
+
+/// A number of bytes, optionally with a K, M or G suffix of powers of 1024, e.g. `512K` or `10MB`.
+#[derive(Debug, Clone, Copy)]
+pub struct ByteSize(pub u64);
+
+impl FromStr for ByteSize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_uppercase();
+        let number = upper.trim_end_matches("IB").trim_end_matches('B');
+        let (number, unit) = match number.char_indices().last() {
+            Some((i, 'K')) => (&number[..i], 1 << 10),
+            Some((i, 'M')) => (&number[..i], 1 << 20),
+            Some((i, 'G')) => (&number[..i], 1 << 30),
+            _ => (number, 1),
+        };
+        number
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .and_then(|number| number.checked_mul(unit))
+            .map(ByteSize)
+            .ok_or_else(|| Error::InvalidArg(s.to_string()))
+    }
+}
+
+/// How many leading path components of a file make up its directory, at least one.
+#[derive(Debug, Clone, Copy)]
+pub struct Depth(pub usize);
+
+impl FromStr for Depth {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse::<usize>() {
+            Ok(depth) if depth > 0 => Ok(Depth(depth)),
+            _ => Err(Error::InvalidArg(format!("{}, the depth is at least 1", s))),
+        }
+    }
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(
     name = "rcloc",
@@ -82,13 +152,15 @@ impl FromStr for OrderBy {
 pub struct Options {
     #[structopt(
         long = "output",
+        global = true,
         default_value = "Terminal",
-        help = "alternative parameters: Terminal, Markdown\n"
+        help = "alternative parameters: Terminal, Markdown, Json, Html, Yaml, Xml, Csv, Tsv\n"
     )]
     pub output: Output,
 
     #[structopt(
         long = "sort-by",
+        global = true,
         default_value = "language",
         help = "alternative parameters: language, files, size, blank, comment, code\n"
     )]
@@ -96,16 +168,282 @@ pub struct Options {
 
     #[structopt(
         long = "order-by",
+        global = true,
         default_value = "asc",
         help = "alternative parameters: asc, desc\n"
     )]
     pub order_by: OrderBy,
 
-    #[structopt(name = "path", parse(from_os_str))]
-    pub entry: Option<PathBuf>,
+    #[structopt(
+        name = "path",
+        parse(from_os_str),
+        help = "files or directories to count, the current directory by default\n"
+    )]
+    pub entries: Vec<PathBuf>,
+
+    #[structopt(
+        long = "files-from",
+        parse(from_os_str),
+        help = "also count the paths listed in this file, one per line or NUL separated, `-` reads them from stdin\n"
+    )]
+    pub files_from: Option<PathBuf>,
 
     // #[structopt(long = "ignore")]
     // pub ignore_path: Option<PathBuf>,
-    #[structopt(long = "ignore-file")]
+    #[structopt(long = "ignore-file", global = true)]
     pub ignore_file: Option<PathBuf>,
+
+    #[structopt(
+        long = "vcs",
+        global = true,
+        help = "only count files tracked by the version control system instead of walking the tree, alternative parameters: git\n"
+    )]
+    pub vcs: Option<Vcs>,
+
+    #[structopt(
+        long = "no-ignore",
+        global = true,
+        help = "do not skip files matched by .gitignore, .ignore, .git/info/exclude or git's core.excludesFile\n"
+    )]
+    pub no_ignore: bool,
+
+    #[structopt(
+        long = "follow-symlinks",
+        global = true,
+        help = "walk into symlinked files and directories, each directory is counted once however it is reached\n"
+    )]
+    pub follow_symlinks: bool,
+
+    #[structopt(
+        long = "one-file-system",
+        global = true,
+        help = "do not walk into directories on other file systems than the entry, e.g. mounted volumes\n"
+    )]
+    pub one_file_system: bool,
+
+    #[structopt(
+        long = "max-depth",
+        global = true,
+        help = "do not walk deeper than this many directories below the entry, 1 counts only the files right in it\n"
+    )]
+    pub max_depth: Option<usize>,
+
+    #[structopt(
+        long = "no-hidden",
+        global = true,
+        overrides_with = "hidden",
+        help = "skip files and directories whose name starts with a dot\n"
+    )]
+    pub no_hidden: bool,
+
+    #[structopt(
+        long = "hidden",
+        global = true,
+        overrides_with = "no-hidden",
+        help = "count files and directories whose name starts with a dot, the default\n"
+    )]
+    pub hidden: bool,
+
+    #[structopt(
+        long = "max-file-size",
+        global = true,
+        help = "skip source files larger than this, e.g. 512K or 10M\n"
+    )]
+    pub max_file_size: Option<ByteSize>,
+
+    #[structopt(
+        long = "include-lang",
+        global = true,
+        use_delimiter = true,
+        number_of_values = 1,
+        help = "only count these languages, comma separated, e.g. Rust,C\n"
+    )]
+    pub include_lang: Vec<String>,
+
+    #[structopt(
+        long = "exclude-lang",
+        global = true,
+        use_delimiter = true,
+        number_of_values = 1,
+        help = "do not count these languages, comma separated\n"
+    )]
+    pub exclude_lang: Vec<String>,
+
+    #[structopt(
+        long = "include-ext",
+        global = true,
+        use_delimiter = true,
+        number_of_values = 1,
+        help = "only count files with these extensions, comma separated, e.g. rs,c\n"
+    )]
+    pub include_ext: Vec<String>,
+
+    #[structopt(
+        long = "exclude-ext",
+        global = true,
+        use_delimiter = true,
+        number_of_values = 1,
+        help = "do not count files with these extensions, comma separated\n"
+    )]
+    pub exclude_ext: Vec<String>,
+
+    #[structopt(
+        long = "exclude-dir",
+        global = true,
+        use_delimiter = true,
+        number_of_values = 1,
+        help = "do not descend into directories with these names, comma separated, e.g. node_modules,vendor\n"
+    )]
+    pub exclude_dir: Vec<String>,
+
+    #[structopt(
+        long = "match-f",
+        global = true,
+        help = "only count files whose name matches this regex, e.g. '\\.(c|h)$'\n"
+    )]
+    pub match_f: Option<String>,
+
+    #[structopt(
+        long = "not-match-f",
+        global = true,
+        help = "do not count files whose name matches this regex, e.g. '_test\\.go$'\n"
+    )]
+    pub not_match_f: Option<String>,
+
+    #[structopt(
+        long = "match-d",
+        global = true,
+        help = "only count files in directories whose path, relative to the entry, matches this regex\n"
+    )]
+    pub match_d: Option<String>,
+
+    #[structopt(
+        long = "not-match-d",
+        global = true,
+        help = "do not descend into directories whose path, relative to the entry, matches this regex\n"
+    )]
+    pub not_match_d: Option<String>,
+
+    #[structopt(
+        long = "stdin-lang",
+        conflicts_with_all = &["path", "files-from"],
+        help = "count the source read from stdin as this language, e.g. Rust\n"
+    )]
+    pub stdin_lang: Option<String>,
+
+    #[structopt(
+        long = "stdin-name",
+        parse(from_os_str),
+        conflicts_with_all = &["path", "files-from"],
+        help = "count the source read from stdin as a file of this name, its extension tells the language unless --stdin-lang is given\n"
+    )]
+    pub stdin_name: Option<PathBuf>,
+
+    #[structopt(
+        long = "verbose",
+        global = true,
+        help = "list every file that could not be read, with the reason\n"
+    )]
+    pub verbose: bool,
+
+    #[structopt(
+        long = "strict",
+        global = true,
+        help = "exit with status 1 when any file could not be read\n"
+    )]
+    pub strict: bool,
+
+    #[structopt(
+        long = "report-unknown",
+        help = "also list the files of no known language, by extension or by name when they have none\n"
+    )]
+    pub report_unknown: bool,
+
+    #[structopt(
+        long = "by-file",
+        help = "report every counted file instead of one row per language\n"
+    )]
+    pub by_file: bool,
+
+    #[structopt(
+        long = "by-directory",
+        conflicts_with = "by-file",
+        help = "report one row per directory instead of one row per language\n"
+    )]
+    pub by_directory: bool,
+
+    #[structopt(
+        long = "depth",
+        default_value = "1",
+        help = "with --by-directory, how many leading path components make up a directory, at least 1\n"
+    )]
+    pub depth: Depth,
+
+    #[structopt(
+        long = "breakdown",
+        help = "with --by-directory, also list the languages inside each directory\n"
+    )]
+    pub breakdown: bool,
+
+    #[structopt(
+        long = "baseline",
+        parse(from_os_str),
+        conflicts_with_all = &["by-file", "by-directory"],
+        help = "a report saved with --output json to show per language deltas against\n"
+    )]
+    pub baseline: Option<PathBuf>,
+
+    #[structopt(long = "no-sum", global = true, help = "omit the Sum row from csv and tsv output\n")]
+    pub no_sum: bool,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum Command {
+    #[structopt(about = "compare two trees and report same, modified, added and removed lines per language")]
+    Diff {
+        #[structopt(parse(from_os_str))]
+        old: PathBuf,
+
+        #[structopt(parse(from_os_str))]
+        new: PathBuf,
+    },
+
+    #[structopt(about = "add up reports saved with --output json, e.g. one per repository, into a single report")]
+    SumReports {
+        #[structopt(parse(from_os_str), required = true, min_values = 1)]
+        reports: Vec<PathBuf>,
+
+        #[structopt(long = "by-source", help = "also list the languages of every report\n")]
+        by_source: bool,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_size() {
+        let size = |s: &str| s.parse::<ByteSize>().ok().map(|size| size.0);
+
+        assert_eq!(size("1000"), Some(1000));
+        assert_eq!(size("512K"), Some(512 * 1024));
+        assert_eq!(size("10MB"), Some(10 * 1024 * 1024));
+        assert_eq!(size("1gib"), Some(1024 * 1024 * 1024));
+        assert_eq!(size("1.5M"), None);
+        assert_eq!(size("M"), None);
+    }
+
+    #[test]
+    fn test_depth() {
+        let depth = |s: &str| s.parse::<Depth>().ok().map(|depth| depth.0);
+
+        assert_eq!(depth("1"), Some(1));
+        assert_eq!(depth("3"), Some(3));
+        assert_eq!(depth("0"), None);
+        assert_eq!(depth("-1"), None);
+    }
 }
@@ -1,12 +1,45 @@
 use std::time::Duration;
 
-use crate::reporter::Report;
+use crate::baseline::{Baseline, Delta};
+use crate::calculator::{Detail, FileDetail};
+use crate::differ::{DiffDetail, DiffReport};
+use crate::reporter::{DirectoryDetail, Report, UnknownFiles};
 use crate::util::bytes_to_size;
 
-pub fn pretty_print(report: Report, elapsed: Duration) {
-    let Report { sections, summary } = report;
+pub fn pretty_print(mut report: Report, elapsed: Duration) {
+    let unknown = report.unknown.take();
+    pretty_print_report(report, elapsed);
+    if let Some(unknown) = unknown {
+        pretty_print_unknown(unknown);
+    }
+}
+
+fn pretty_print_report(report: Report, elapsed: Duration) {
+    let Report {
+        sections,
+        files,
+        directories,
+        summary,
+        sources,
+        baseline,
+        ..
+    } = report;
 
     println!("{:>12.4} secs", elapsed.as_secs_f64());
+
+    if let Some(files) = files {
+        return pretty_print_files(files, summary);
+    }
+    if let Some(directories) = directories {
+        return pretty_print_directories(directories, summary, "Directory");
+    }
+    if let Some(sources) = sources {
+        return pretty_print_directories(sources, summary, "Source");
+    }
+    if let Some(baseline) = baseline {
+        return pretty_print_baseline(sections, summary, baseline);
+    }
+
     println!("┌───────────────────────────────────────────────────────────────────────────────────────┐");
     println!(
         "│ {:<25}{:>12}{:>12}{:>12}{:>12}{:>12} │",
@@ -38,3 +71,181 @@ pub fn pretty_print(report: Report, elapsed: Duration) {
     );
     println!("└───────────────────────────────────────────────────────────────────────────────────────┘");
 }
+
+fn pretty_print_unknown(unknown: Vec<UnknownFiles>) {
+    let width = unknown
+        .iter()
+        .map(|files| files.name.chars().count() + 2)
+        .fold(25, usize::max);
+    let line = "─".repeat(width + 12 * 2 + 2);
+
+    println!("┌{}┐", line);
+    println!("│ {:<width$}{:>12}{:>12} │", "Unknown", "files", "size", width = width);
+    println!("├{}┤", line);
+    for files in unknown {
+        println!(
+            "│ {:<width$}{:>12}{:>12} │",
+            files.name,
+            files.files,
+            bytes_to_size(files.bytes as f64),
+            width = width
+        );
+    }
+    println!("└{}┘", line);
+}
+
+fn pretty_print_baseline(sections: Vec<Detail>, summary: Detail, baseline: Baseline) {
+    let line = "─".repeat(25 + 12 * 7 + 2);
+    let print_row = |name: &str, detail: &Detail, previous: &Detail| {
+        let (code, percent) = Delta::new(previous, detail).code_columns();
+        println!(
+            "│ {:<25}{:>12}{:>12}{:>12}{:>12}{:>12}{:>12}{:>12} │",
+            name,
+            detail.files,
+            bytes_to_size(detail.bytes as f64),
+            detail.blank,
+            detail.comment,
+            detail.code,
+            code,
+            percent,
+        );
+    };
+
+    println!("┌{}┐", line);
+    println!(
+        "│ {:<25}{:>12}{:>12}{:>12}{:>12}{:>12}{:>12}{:>12} │",
+        "Language", "files", "size", "blank", "comment", "code", "Δ code", "Δ code %",
+    );
+    println!("├{}┤", line);
+
+    for detail in &sections {
+        print_row(detail.language, detail, &baseline.previous(detail.language));
+    }
+
+    println!("├{}┤", line);
+    print_row("Sum", &summary, &baseline.summary);
+    println!("└{}┘", line);
+}
+
+// File paths have no upper bound on their length, so the first column grows to fit the longest one.
+fn pretty_print_files(files: Vec<FileDetail>, summary: Detail) {
+    let width = files
+        .iter()
+        .map(|file| file.path.to_string_lossy().chars().count() + 2)
+        .fold(25, usize::max);
+    let line = "─".repeat(width + 25 + 14 + 12 * 3 + 2);
+
+    println!("┌{}┐", line);
+    println!(
+        "│ {:<width$}{:<25}{:<14}{:>12}{:>12}{:>12} │",
+        "File",
+        "Language",
+        "Encoding",
+        "blank",
+        "comment",
+        "code",
+        width = width
+    );
+    println!("├{}┤", line);
+
+    for file in files {
+        let FileDetail { path, detail, encoding } = file;
+        println!(
+            "│ {:<width$}{:<25}{:<14}{:>12}{:>12}{:>12} │",
+            path.to_string_lossy(),
+            detail.language,
+            encoding.to_string(),
+            detail.blank,
+            detail.comment,
+            detail.code,
+            width = width
+        );
+    }
+
+    println!("├{}┤", line);
+    println!(
+        "│ {:<width$}{:<25}{:<14}{:>12}{:>12}{:>12} │",
+        "Sum",
+        "",
+        "",
+        summary.blank,
+        summary.comment,
+        summary.code,
+        width = width
+    );
+    println!("└{}┘", line);
+}
+
+// Languages of a directory are listed indented right below its total row.
+fn pretty_print_directories(directories: Vec<DirectoryDetail>, summary: Detail, heading: &str) {
+    let width = directories
+        .iter()
+        .map(|directory| directory.path.to_string_lossy().chars().count() + 2)
+        .fold(25, usize::max);
+    let line = "─".repeat(width + 12 * 5 + 2);
+    let print_row = |name: &str, detail: &Detail| {
+        println!(
+            "│ {:<width$}{:>12}{:>12}{:>12}{:>12}{:>12} │",
+            name,
+            detail.files,
+            bytes_to_size(detail.bytes as f64),
+            detail.blank,
+            detail.comment,
+            detail.code,
+            width = width
+        );
+    };
+
+    println!("┌{}┐", line);
+    println!(
+        "│ {:<width$}{:>12}{:>12}{:>12}{:>12}{:>12} │",
+        heading,
+        "files",
+        "size",
+        "blank",
+        "comment",
+        "code",
+        width = width
+    );
+    println!("├{}┤", line);
+
+    for directory in directories {
+        print_row(&directory.path.to_string_lossy(), &directory.total);
+        for detail in &directory.languages {
+            print_row(&format!("  {}", detail.language), detail);
+        }
+    }
+
+    println!("├{}┤", line);
+    print_row("Sum", &summary);
+    println!("└{}┘", line);
+}
+
+pub fn pretty_print_diff(report: DiffReport, elapsed: Duration) {
+    let DiffReport { sections, summary, .. } = report;
+    let print_group = |diff: &DiffDetail| {
+        println!("│ {:<73} │", diff.language);
+        for (status, detail) in diff.rows().iter() {
+            println!(
+                "│   {:<23}{:>12}{:>12}{:>12}{:>12} │",
+                status, detail.files, detail.blank, detail.comment, detail.code,
+            );
+        }
+    };
+
+    println!("{:>12.4} secs", elapsed.as_secs_f64());
+    println!("┌───────────────────────────────────────────────────────────────────────────┐");
+    println!(
+        "│ {:<25}{:>12}{:>12}{:>12}{:>12} │",
+        "Language", "files", "blank", "comment", "code",
+    );
+    println!("├───────────────────────────────────────────────────────────────────────────┤");
+
+    for diff in &sections {
+        print_group(diff);
+    }
+
+    println!("├───────────────────────────────────────────────────────────────────────────┤");
+    print_group(&summary);
+    println!("└───────────────────────────────────────────────────────────────────────────┘");
+}
@@ -1,34 +1,142 @@
 use std::collections::HashMap;
 use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Receiver;
+use std::time::Duration;
 
-use crate::calculator::Detail;
+use crate::baseline::Baseline;
+use crate::calculator::{Counted, Detail, FileDetail, FileError};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Granularity {
+    Language,
+    File,
+    Directory { depth: usize, breakdown: bool },
+}
 
 pub struct Reporter {
-    receiver: Receiver<Detail>,
+    receiver: Receiver<Counted>,
+    root: PathBuf,
+    granularity: Granularity,
 }
 
 impl Reporter {
-    pub fn new(receiver: Receiver<Detail>) -> Self {
-        Self { receiver }
+    pub fn new(receiver: Receiver<Counted>, root: PathBuf, granularity: Granularity) -> Self {
+        Self {
+            receiver,
+            root,
+            granularity,
+        }
     }
 
     pub fn research(&self) -> Report {
         let mut kinds = HashMap::<&str, Detail>::new();
         let mut summary = Detail::new("Sum", 0, 0, 0, 0, 0);
+        let mut files = Vec::<FileDetail>::new();
+        let mut directories = HashMap::<PathBuf, DirectoryDetail>::new();
+        let mut failures = Vec::<FileError>::new();
+        let mut binaries = Vec::<PathBuf>::new();
 
-        for detail in &self.receiver {
+        for counted in &self.receiver {
+            let mut file = match counted {
+                Counted::File(file) => file,
+                Counted::Binary(path) => {
+                    binaries.push(path.strip_prefix(&self.root).unwrap_or(&path).to_path_buf());
+                    continue;
+                }
+                Counted::Failed(mut failure) => {
+                    if let Ok(relative) = failure.path.strip_prefix(&self.root) {
+                        failure.path = relative.to_path_buf();
+                    }
+                    failures.push(failure);
+                    continue;
+                }
+            };
+            let detail = file.detail;
             summary += detail;
 
             kinds
                 .entry(detail.language)
                 .and_modify(|acc| acc.add_assign(detail))
                 .or_insert(detail);
+
+            if let Ok(relative) = file.path.strip_prefix(&self.root) {
+                file.path = relative.to_path_buf();
+            }
+
+            match self.granularity {
+                Granularity::Language => {}
+                Granularity::File => files.push(file),
+                Granularity::Directory { depth, breakdown } => {
+                    let directory = Self::directory_prefix(&file.path, depth);
+                    directories
+                        .entry(directory)
+                        .or_insert_with_key(|path| DirectoryDetail::new(path.clone()))
+                        .add_assign(detail, breakdown);
+                }
+            }
         }
 
         Report {
-            sections: kinds.into_iter().map(|(_, value)| value).collect(),
+            sections: kinds.into_values().collect(),
+            files: match self.granularity {
+                Granularity::File => Some(files),
+                _ => None,
+            },
+            directories: match self.granularity {
+                Granularity::Directory { .. } => Some(directories.into_values().collect()),
+                _ => None,
+            },
             summary,
+            sources: None,
+            baseline: None,
+            unmatched_ignores: vec![],
+            skipped: Skipped::default(),
+            binaries,
+            unknown: None,
+            failures,
+        }
+    }
+
+    // The first `depth` components of the directory holding `path`, files right under the entry roll up into ".".
+    fn directory_prefix(path: &Path, depth: usize) -> PathBuf {
+        let prefix = path
+            .parent()
+            .map(|parent| parent.components().take(depth).collect::<PathBuf>())
+            .unwrap_or_default();
+
+        if prefix.as_os_str().is_empty() {
+            PathBuf::from(".")
+        } else {
+            prefix
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DirectoryDetail {
+    pub path: PathBuf,
+    pub total: Detail,
+    pub languages: Vec<Detail>,
+}
+
+impl DirectoryDetail {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            total: Detail::new("Sum", 0, 0, 0, 0, 0),
+            languages: vec![],
+        }
+    }
+
+    fn add_assign(&mut self, detail: Detail, breakdown: bool) {
+        self.total += detail;
+
+        if breakdown {
+            match self.languages.iter_mut().find(|acc| acc.language == detail.language) {
+                Some(acc) => *acc += detail,
+                None => self.languages.push(detail),
+            }
         }
     }
 }
@@ -36,5 +144,146 @@ impl Reporter {
 #[derive(Debug)]
 pub struct Report {
     pub sections: Vec<Detail>,
+    pub files: Option<Vec<FileDetail>>,
+    pub directories: Option<Vec<DirectoryDetail>>,
     pub summary: Detail,
+    /// Per report languages of `rcloc sum-reports --by-source`.
+    pub sources: Option<Vec<DirectoryDetail>>,
+    pub baseline: Option<Baseline>,
+    /// Patterns of --ignore-file that matched nothing.
+    pub unmatched_ignores: Vec<String>,
+    pub skipped: Skipped,
+    /// Files with a source extension but binary content, skipped whatever the walk options.
+    pub binaries: Vec<PathBuf>,
+    /// Files of no known language, with --report-unknown.
+    pub unknown: Option<Vec<UnknownFiles>>,
+    /// Files that could not be read, left out of the counts.
+    pub failures: Vec<FileError>,
+}
+
+/// Files of no known language sharing an extension, written as `.ext`, or a name when they have none.
+#[derive(Debug, Clone)]
+pub struct UnknownFiles {
+    pub name: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Files left out by the walk limits, rather than by the filters.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Skipped {
+    /// Hidden files and the files below hidden directories, with --no-hidden.
+    pub hidden: usize,
+    /// Files below --max-depth.
+    pub too_deep: usize,
+    /// Source files larger than --max-file-size.
+    pub too_large: usize,
+}
+
+impl Skipped {
+    pub fn is_empty(&self) -> bool {
+        self.hidden == 0 && self.too_deep == 0 && self.too_large == 0
+    }
+}
+
+impl AddAssign for Skipped {
+    fn add_assign(&mut self, rhs: Self) {
+        self.hidden += rhs.hidden;
+        self.too_deep += rhs.too_deep;
+        self.too_large += rhs.too_large;
+    }
+}
+
+impl Report {
+    /// Adds up reports saved with `--output json`, `by_source` keeps the languages of every report apart as well.
+    pub fn merge(saved: Vec<Baseline>, by_source: bool) -> Self {
+        let mut kinds = HashMap::<&str, Detail>::new();
+        let mut summary = Detail::new("Sum", 0, 0, 0, 0, 0);
+        let mut sources = Vec::<DirectoryDetail>::new();
+
+        for report in saved {
+            let mut source = DirectoryDetail::new(report.path);
+            summary += report.summary;
+
+            for detail in report.sections.into_values() {
+                kinds
+                    .entry(detail.language)
+                    .and_modify(|acc| acc.add_assign(detail))
+                    .or_insert(detail);
+                source.add_assign(detail, true);
+            }
+            sources.push(source);
+        }
+
+        Report {
+            sections: kinds.into_values().collect(),
+            files: None,
+            directories: None,
+            summary,
+            sources: if by_source { Some(sources) } else { None },
+            baseline: None,
+            unmatched_ignores: vec![],
+            skipped: Skipped::default(),
+            binaries: vec![],
+            unknown: None,
+            failures: vec![],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Metadata {
+    pub entry: PathBuf,
+    pub elapsed: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directory_prefix() {
+        assert_eq!(Reporter::directory_prefix(Path::new("main.rs"), 1), PathBuf::from("."));
+        assert_eq!(
+            Reporter::directory_prefix(Path::new("src/main.rs"), 1),
+            PathBuf::from("src")
+        );
+        assert_eq!(
+            Reporter::directory_prefix(Path::new("a/b/c/lib.rs"), 2),
+            PathBuf::from("a/b")
+        );
+        assert_eq!(
+            Reporter::directory_prefix(Path::new("a/b/c/lib.rs"), 5),
+            PathBuf::from("a/b/c")
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let saved = |path: &str, sections: &[Detail]| Baseline {
+            path: PathBuf::from(path),
+            sections: sections.iter().map(|detail| (detail.language, *detail)).collect(),
+            summary: sections
+                .iter()
+                .fold(Detail::new("Sum", 0, 0, 0, 0, 0), |mut acc, detail| {
+                    acc += *detail;
+                    acc
+                }),
+        };
+        let first = saved(
+            "a.json",
+            &[Detail::new("Rust", 2, 100, 1, 2, 30), Detail::new("C", 1, 10, 0, 0, 5)],
+        );
+        let second = saved("b.json", &[Detail::new("Rust", 1, 50, 1, 0, 10)]);
+
+        let report = Report::merge(vec![first, second], true);
+        let rust = report.sections.iter().find(|detail| detail.language == "Rust").unwrap();
+
+        assert_eq!(
+            (rust.files, rust.bytes, rust.blank, rust.comment, rust.code),
+            (3, 150, 2, 2, 40)
+        );
+        assert_eq!((report.summary.files, report.summary.code), (4, 45));
+        assert_eq!(report.sources.map(|sources| sources.len()), Some(2));
+    }
 }
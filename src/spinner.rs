@@ -1,6 +1,6 @@
 #![allow(clippy::mutex_atomic)]
 use std::io::prelude::*;
-use std::io::stdout;
+use std::io::stderr;
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread::{sleep, spawn};
 use std::time::Duration;
@@ -21,10 +21,10 @@ impl Spinner {
 
     pub fn start(&self) {
         let Self { cvar, lock } = self;
-        let pair = (Arc::clone(&lock), Arc::clone(&cvar));
+        let pair = (Arc::clone(lock), Arc::clone(cvar));
         spawn(move || {
-            let mut out = stdout();
-            for c in vec!['|', '/', '-', '\\'].iter().cycle() {
+            let mut out = stderr();
+            for c in ['|', '/', '-', '\\'].iter().cycle() {
                 let status = format!("{} computing", c);
                 let _ = out.write_all(status.as_bytes());
                 let _ = out.flush();
@@ -1,6 +1,10 @@
 use std::cmp::Ordering;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 
-use crate::options::OrderBy;
+use crate::calculator::Detail;
+use crate::options::{OrderBy, SortBy};
 
 #[inline]
 pub fn compare<T: Ord>(t1: T, t2: T, order_by: OrderBy) -> Ordering {
@@ -12,6 +16,17 @@ pub fn compare<T: Ord>(t1: T, t2: T, order_by: OrderBy) -> Ordering {
     t1.cmp(&t2)
 }
 
+pub fn compare_detail(prev: &Detail, next: &Detail, sort_by: SortBy, order_by: OrderBy) -> Ordering {
+    match sort_by {
+        SortBy::Language => compare(prev.language, next.language, order_by),
+        SortBy::Files => compare(prev.files, next.files, order_by),
+        SortBy::Size => compare(prev.bytes, next.bytes, order_by),
+        SortBy::Blank => compare(prev.blank, next.blank, order_by),
+        SortBy::Comment => compare(prev.comment, next.comment, order_by),
+        SortBy::Code => compare(prev.code, next.code, order_by),
+    }
+}
+
 const SIZES: [&str; 9] = ["B", "KB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
 
 pub fn bytes_to_size(bytes: f64) -> String {
@@ -23,6 +38,51 @@ pub fn bytes_to_size(bytes: f64) -> String {
     format!("{:.2} {}", bytes / k.powi(i), SIZES[i as usize])
 }
 
+/// Escapes the characters that are significant in HTML and XML text and attribute values.
+pub fn escape_markup(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `path` with its components joined by `/` on every platform, as patterns expect it.
+pub fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The paths listed in `source`, or on stdin for `-`: NUL separated when there is any NUL, one per line otherwise.
+pub fn read_path_list(source: &Path) -> io::Result<Vec<PathBuf>> {
+    let content = if source == Path::new("-") {
+        let mut content = vec![];
+        io::stdin().read_to_end(&mut content)?;
+        content
+    } else {
+        fs::read(source)?
+    };
+
+    Ok(split_path_list(&content))
+}
+
+fn split_path_list(content: &[u8]) -> Vec<PathBuf> {
+    let separator = if content.contains(&0) { 0 } else { b'\n' };
+    content
+        .split(|&byte| byte == separator)
+        .map(|line| {
+            if separator == b'\n' {
+                line.strip_suffix(b"\r").unwrap_or(line)
+            } else {
+                line
+            }
+        })
+        .filter(|line| !line.is_empty())
+        .map(|line| PathBuf::from(&*String::from_utf8_lossy(line)))
+        .collect()
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -34,4 +94,16 @@ pub mod tests {
 
         assert_eq!(compare(a, b, OrderBy::Asc), Ordering::Less);
     }
+
+    #[test]
+    pub fn test_split_path_list() {
+        assert_eq!(
+            split_path_list(b"src/main.rs\r\n\nbuild.rs\n"),
+            vec![PathBuf::from("src/main.rs"), PathBuf::from("build.rs")]
+        );
+        assert_eq!(
+            split_path_list(b"with\nnewline.rs\0b.rs\0"),
+            vec![PathBuf::from("with\nnewline.rs"), PathBuf::from("b.rs")]
+        );
+    }
 }
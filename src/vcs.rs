@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::Error;
+use crate::options::Vcs;
+use crate::Result;
+
+/// Files below `entry` that are tracked by `vcs`, as absolute paths.
+pub fn tracked_files(vcs: Vcs, entry: &Path) -> Result<Vec<PathBuf>> {
+    match vcs {
+        Vcs::Git => git_ls_files(entry),
+    }
+}
+
+// `git ls-files` lists the index relative to the directory it runs in, limited to that directory.
+fn git_ls_files(entry: &Path) -> Result<Vec<PathBuf>> {
+    let mut command = Command::new("git");
+    let directory = if entry.is_dir() {
+        command.arg("-C").arg(entry).args(["ls-files", "-z"]);
+        entry
+    } else {
+        let directory = entry.parent().unwrap_or_else(|| Path::new("."));
+        command
+            .arg("-C")
+            .arg(directory)
+            .args(["ls-files", "-z", "--"])
+            .arg(entry);
+        directory
+    };
+
+    let output = command
+        .output()
+        .map_err(|err| Error::Vcs(format!("could not run git: {}", err)))?;
+    if !output.status.success() {
+        return Err(Error::Vcs(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    Ok(split_nul(directory, &output.stdout))
+}
+
+fn split_nul(directory: &Path, stdout: &[u8]) -> Vec<PathBuf> {
+    stdout
+        .split(|&byte| byte == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| directory.join(&*String::from_utf8_lossy(name)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_nul() {
+        let files = split_nul(Path::new("/repo"), b"src/main.rs\0with space.md\0");
+        assert_eq!(
+            files,
+            vec![PathBuf::from("/repo/src/main.rs"), PathBuf::from("/repo/with space.md")]
+        );
+        assert!(split_nul(Path::new("/repo"), b"").is_empty());
+    }
+}